@@ -1,10 +1,28 @@
 
 use chrono::prelude::*;
+use clap::ArgMatches;
+use clap::Args;
+use clap::CommandFactory;
+use clap::FromArgMatches;
+use clap::Parser;
+use clap::Subcommand;
+use clap::ValueEnum;
+use clap::builder::ArgGroup;
+use clap::builder::PossibleValuesParser;
+use clap::builder::RangedU64ValueParser;
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::value_parser;
 use crossbeam_queue::ArrayQueue;
 //use flate2::Compression;
 use flate2::read::GzDecoder;
 //use flate2::write::GzEncoder;
 use rand::prelude::*;
+use serde::Deserialize;
+use serde::Serialize;
+use std::any::Any;
+use std::borrow::Cow;
+use std::cell::RefCell;
 use std::collections::BTreeSet;
 use std::collections::BTreeMap;
 use std::env;
@@ -12,11 +30,20 @@ use std::fs::File;
 use std::io::BufRead;
 use std::io::BufReader;
 use std::io::BufWriter;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
 use std::io::Write;
+use std::path::Path;
 use std::path::PathBuf;
+use std::panic::AssertUnwindSafe;
+use std::panic::catch_unwind;
 use std::process::exit;
 use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::OnceLock;
 use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
 use std::thread;
 use std::thread::JoinHandle;
@@ -25,9 +52,26 @@ use std::time::Instant;
 
 // minus x, plus x, minus y, plus y, minus z, plus z
 const DIRECTIONS: [usize; 6] = [0, 1, 2, 3, 4, 5];
+// a cube's position packs its x, y and z coordinates into a single
+//   integer, as x + y*Y_STEP + z*Z_STEP, with COORDINATE_BITS bits for
+//   each of x and y (and the rest for z), so moving to a neighbor is
+//   just adding the step for that direction
+const COORDINATE_BITS: u32 = 16;
+const X_STEP: isize = 1;
+const Y_STEP: isize = 1 << COORDINATE_BITS;
+const Z_STEP: isize = 1 << (2 * COORDINATE_BITS);
+// the farthest a cube can be from the origin along any axis, checked
+//   in add()
+// this leaves room for a neighbor of a cube at the limit to still be
+//   unpacked correctly (and be rejected by add())
+pub const MAX_COORDINATE: isize = (1 << (COORDINATE_BITS - 1)) - 2;
+// the packing needs room for 3 coordinate fields
+const _: () = assert!(isize::BITS >= 3 * COORDINATE_BITS);
+// the position of the first cube in a polycube
+const ORIGIN_POS: isize = 0;
 // used to create a unique integer position for each cube
 //   in a polycube
-const DIRECTION_COSTS: [isize; 6] = [-1, 1, -100, 100, -10_000, 10_000];
+const DIRECTION_COSTS: [isize; 6] = [-X_STEP, X_STEP, -Y_STEP, Y_STEP, -Z_STEP, Z_STEP];
 // each of the 24 possible rotations of a 3d object
 //  (where each value refers to one of the above directions)
 const ROTATIONS: [[usize; 6]; 24] = [
@@ -216,10 +260,32 @@ const WELL_KNOWN_N_COUNTS: [usize; 23] = [0,
 // store counts for 0 cubes, 1 cube, 2 cubes, etc, up to MAX_N-1
 const MAX_N: usize = 22+1;
 
-// since we offset in the +x direction by 1 unit for each cube,
-//   and enumerating cubes for n=50 is way beyond what's possible,
-//   we can use pos=50 as a placeholder for an "impossible" pos
-const IMPOSSIBLE_POS: isize = 50;
+// jobs are handed to worker threads as canonical encodings through
+//   a small bounded queue, so the initial delegator (or the thread
+//   feeding jobs from a file) waits for the workers to catch up when
+//   the queue is full instead of buffering every spawn_n polycube
+const JOB_QUEUE_CAPACITY: usize = 4096;
+// each job produces one response, and the main thread drains the
+//   responses once per second, so this needs to hold about a
+//   second's worth of completed jobs before workers have to wait
+const RESPONSE_QUEUE_CAPACITY: usize = 16_384;
+// how long a thread waits before retrying a push to a full queue
+const QUEUE_FULL_WAIT: Duration = Duration::from_millis(2);
+// a job that panics is tried once more before it's quarantined
+const JOB_ATTEMPTS: usize = 2;
+// how often a progress event is written, with --json
+const PROGRESS_EVENT_SEC: f64 = 10.0;
+// the trailer that closes a resume file, and each batch appended to a
+//   polycubes file, followed by the number of records it closes, so
+//   that a file cut short by a crash is never mistaken for a whole one
+const END_MARKER: &str = "--end--";
+// the random walks taken to estimate how many of the jobs a run's
+//   initial delegator will find have each set of features, which
+//   takes a few seconds (see sample_job_census)
+const CENSUS_WALKS: usize = 10_000;
+// the random walks weighed for each sampled polycube by default,
+//   which take about a second at n=15 (see sample_polycube)
+const SAMPLE_WALKS: usize = 1000;
 
 const POLYCUBE_ENCODING_ALPHABET: [char; 94] = [
 	'0','1','2','3','4','5','6','7','8','9',
@@ -234,6 +300,24 @@ const POLYCUBE_ENCODING_ALPHABET: [char; 94] = [
 	'.','?','~','`'
 ];
 
+pub fn pos_from_coordinates(x: isize, y: isize, z: isize) -> isize {
+	x * X_STEP + y * Y_STEP + z * Z_STEP
+}
+
+// unpack a position, where each field is sign-extended in turn
+pub fn pos_to_coordinates(pos: isize) -> (isize, isize, isize) {
+	let shift = isize::BITS - COORDINATE_BITS;
+	let x = (pos << shift) >> shift;
+	let y = (((pos - x) >> COORDINATE_BITS) << shift) >> shift;
+	let z = (pos - x - y * Y_STEP) >> (2 * COORDINATE_BITS);
+	(x, y, z)
+}
+
+pub fn pos_in_bounds(pos: isize) -> bool {
+	let (x, y, z) = pos_to_coordinates(pos);
+	x.abs() <= MAX_COORDINATE && y.abs() <= MAX_COORDINATE && z.abs() <= MAX_COORDINATE
+}
+
 pub fn encoding_to_str(enc: u128) -> String {
 	if enc == 0 {
 		return String::from(POLYCUBE_ENCODING_ALPHABET[0]);
@@ -265,19 +349,406 @@ pub fn str_to_encoding(s: &str) -> u128 {
 	return enc;
 }
 
+// the unit vector for one of the 6 directions
+pub fn direction_unit(direction: usize) -> (isize, isize, isize) {
+	let sign = if direction.is_multiple_of(2) { -1 } else { 1 };
+	match direction / 2 {
+		0 => (sign, 0, 0),
+		1 => (0, sign, 0),
+		_ => (0, 0, sign)
+	}
+}
+
+// the coordinates rotated by one of the ROTATIONS
+pub fn rotated_coordinates(coordinates: &[(isize, isize, isize)], rotation: &[usize; 6]) -> Vec<(isize, isize, isize)> {
+	// where the plus x, plus y and plus z directions end up
+	let (ux, uy, uz) = (direction_unit(rotation[1]), direction_unit(rotation[3]), direction_unit(rotation[5]));
+	coordinates.iter().map(|(x, y, z)| (
+		x * ux.0 + y * uy.0 + z * uz.0,
+		x * ux.1 + y * uy.1 + z * uz.1,
+		x * ux.2 + y * uy.2 + z * uz.2)).collect()
+}
+
+// the coordinates moved so the smallest of each are 0, in sorted order
+pub fn normalized_coordinates(mut coordinates: Vec<(isize, isize, isize)>) -> Vec<(isize, isize, isize)> {
+	let (min_x, min_y, min_z) = minimum_coordinates(&coordinates);
+	for c in coordinates.iter_mut() {
+		*c = (c.0 - min_x, c.1 - min_y, c.2 - min_z);
+	}
+	coordinates.sort_unstable();
+	coordinates
+}
+
+// the smallest of each coordinate
+pub fn minimum_coordinates(coordinates: &[(isize, isize, isize)]) -> (isize, isize, isize) {
+	(coordinates.iter().map(|c| c.0).min().unwrap_or(0),
+		coordinates.iter().map(|c| c.1).min().unwrap_or(0),
+		coordinates.iter().map(|c| c.2).min().unwrap_or(0))
+}
+
+// one of the ROTATIONS followed by an offset, which together move a
+//   polycube back onto itself
+pub struct SymmetryMove {
+	rotation: [usize; 6],
+	offset: (isize, isize, isize)
+}
+
+impl SymmetryMove {
+	pub fn moved(&self, pos: isize) -> isize {
+		let (x, y, z) = rotated_coordinates(&[pos_to_coordinates(pos)], &self.rotation)[0];
+		pos_from_coordinates(x + self.offset.0, y + self.offset.1, z + self.offset.2)
+	}
+}
+
+// the name of the group of rotations given by their indices in
+//   ROTATIONS, which is one of the subgroups of the cube's rotations
+pub fn rotation_group_name(symmetries: &[usize]) -> &'static str {
+	// the order of a rotation is that of its permutation of the directions
+	let rotation_order = |rotation: &[usize; 6]| -> usize {
+		let mut power = *rotation;
+		let mut order = 1;
+		while power != ROTATIONS[0] {
+			power = power.map(|direction| rotation[direction]);
+			order += 1;
+		}
+		order
+	};
+	match symmetries.len() {
+		1 => "C1",
+		2 => "C2",
+		3 => "C3",
+		4 if symmetries.iter().any(|i| rotation_order(&ROTATIONS[*i]) == 4) => "C4",
+		4 => "D2",
+		6 => "D3",
+		8 => "D4",
+		12 => "T",
+		24 => "O",
+		_ => "?"
+	}
+}
+
+// parse a polycube given by its canonical encoding in base94 (as
+//   printed by this program), checking that it is one
+pub fn polycube_from_encoding_str(s: &str) -> Result<Polycube, String> {
+	let mut enc: u128 = 0;
+	for c in s.chars() {
+		let digit = match POLYCUBE_ENCODING_ALPHABET.iter().position(|&x| x == c) {
+			Some(digit) => digit as u128,
+			None => return Err(format!("[{}] is not a polycube encoding: [{}] is not a base94 digit", s, c))
+		};
+		enc = match enc.checked_mul(94).and_then(|enc| enc.checked_add(digit)) {
+			Some(enc) => enc,
+			None => return Err(format!("[{}] is not a polycube encoding: it's too large", s))
+		};
+	}
+	if s.is_empty() {
+		return Err(String::from("an empty string is not a polycube encoding"));
+	}
+	let mut polycube = Polycube::new(false);
+	polycube.rebuild_from_encoding(enc);
+	let canonical_enc = polycube.find_canonical_info(None).enc;
+	if canonical_enc != enc {
+		return Err(format!("[{}] is not a canonical polycube encoding (its polycube encodes to [{}])", s, encoding_to_str(canonical_enc)));
+	}
+	Ok(polycube)
+}
+
+// a lone cube has no neighbors, so its maximum rotated value is 0
+pub fn initial_cube_value_counts() -> [u8; 64] {
+	let mut cube_value_counts = [0; 64];
+	cube_value_counts[MAXIMUM_ROTATED_CUBE_VALUES[0] as usize] = 1;
+	cube_value_counts
+}
+
+// move a cube from the count for its old maximum rotated value to
+//   the count for its new one, after its neighbors have changed
+pub fn update_cube_value_counts(cube_value_counts: &mut [u8; 64], old_enc: isize, new_enc: isize) {
+	cube_value_counts[MAXIMUM_ROTATED_CUBE_VALUES[old_enc as usize] as usize] -= 1;
+	cube_value_counts[MAXIMUM_ROTATED_CUBE_VALUES[new_enc as usize] as usize] += 1;
+}
+
+// detailed counters for the P+A-B search, only kept when built with
+//   the "instrument" feature since updating them slows the search down
+#[cfg(feature = "instrument")]
+#[derive(Clone, Copy, Default)]
+pub struct InstrumentCounters {
+	// positions tried for cube A (not already in P or tried for P)
+	pub candidates_tried: u64,
+	// P+A skipped since another P+A had the same canonical encoding
+	pub duplicate_canonicals: u64,
+	// P+A where cube A is the least significant, so P+A-A=P
+	pub least_significant_fast_path: u64,
+	// P+A-B checks where P+A-B was (or wasn't) the same as P
+	pub pab_checks_passed: u64,
+	pub pab_checks_failed: u64,
+	pub make_encoding_calls: u64,
+	// encodings abandoned as soon as they fell below the best known
+	//   encoding, by the number of cubes encoded at that point
+	pub abandoned_at_depth: [u64; MAX_N]
+}
+
+#[cfg(feature = "instrument")]
+impl InstrumentCounters {
+	pub const fn new() -> InstrumentCounters {
+		InstrumentCounters {
+			candidates_tried: 0,
+			duplicate_canonicals: 0,
+			least_significant_fast_path: 0,
+			pab_checks_passed: 0,
+			pab_checks_failed: 0,
+			make_encoding_calls: 0,
+			abandoned_at_depth: [0; MAX_N]
+		}
+	}
+
+	pub fn add(&mut self, other: &InstrumentCounters) {
+		self.candidates_tried += other.candidates_tried;
+		self.duplicate_canonicals += other.duplicate_canonicals;
+		self.least_significant_fast_path += other.least_significant_fast_path;
+		self.pab_checks_passed += other.pab_checks_passed;
+		self.pab_checks_failed += other.pab_checks_failed;
+		self.make_encoding_calls += other.make_encoding_calls;
+		for depth in 0..MAX_N {
+			self.abandoned_at_depth[depth] += other.abandoned_at_depth[depth];
+		}
+	}
+
+	pub fn print(&self) {
+		println!("instrumentation:");
+		println!("  candidate positions tried.....: {}", self.candidates_tried);
+		println!("  duplicate canonicals rejected.: {}", self.duplicate_canonicals);
+		println!("  A least significant fast path.: {}", self.least_significant_fast_path);
+		println!("  P+A-B checks passed...........: {}", self.pab_checks_passed);
+		println!("  P+A-B checks failed...........: {}", self.pab_checks_failed);
+		println!("  make_encoding calls...........: {}", self.make_encoding_calls);
+		for (depth, count) in self.abandoned_at_depth.iter().enumerate() {
+			if *count > 0 {
+				println!("  encodings abandoned after {: >2} cubes: {}", depth, count);
+			}
+		}
+	}
+
+	pub fn to_json(&self) -> serde_json::Value {
+		serde_json::json!({
+			"candidates_tried": self.candidates_tried,
+			"duplicate_canonicals": self.duplicate_canonicals,
+			"least_significant_fast_path": self.least_significant_fast_path,
+			"pab_checks_passed": self.pab_checks_passed,
+			"pab_checks_failed": self.pab_checks_failed,
+			"make_encoding_calls": self.make_encoding_calls,
+			"abandoned_at_depth": self.abandoned_at_depth
+		})
+	}
+}
+
+// update this thread's instrumentation counters, which compiles
+//   to nothing unless built with the "instrument" feature
+macro_rules! instrument {
+	($counters:ident => $update:expr) => {
+		#[cfg(feature = "instrument")]
+		SEARCH_STATS.with_borrow_mut(|stats| {
+			let $counters = &mut stats.instrument;
+			$update;
+		});
+	};
+}
+
+// the cubes whose maximum rotated value is the largest in a polycube,
+//   which are the only cubes its canonical encoding can start from
+// adding or removing a cube only changes the values of that cube and
+//   its neighbors, so these are kept up to date in Polycube's add()
+//   and remove() rather than found by looking at every cube for each
+//   encoding
+// the start cube and rotation that gave the last canonical encoding
+//   are kept too, for as long as they are still a start cube and one
+//   of its best rotations: adding a cube can change the encoding from
+//   any start cube and rotation (not just the ones near the added cube,
+//   since every later cube in a depth-first encoding shifts), so the
+//   parent's best pair can't rule any others out, but it usually still
+//   gives the child's largest encoding or close to it, so encoding from
+//   it first lets the other pairs be abandoned sooner
+#[derive(Clone, PartialEq)]
+pub struct StartCubes {
+	// in ascending order
+	positions: Vec<isize>,
+	// the maximum rotated value of these cubes
+	value: u8,
+	// false when removing a cube lowered the largest value, since the
+	//   new largest value could belong to any cube, so the start cubes
+	//   have to be found again by looking at all of them
+	known: bool,
+	// the start cube position and ROTATIONS index that gave the last
+	//   canonical encoding found, of this polycube or the one it was
+	//   made from by adding or removing cubes
+	best: Option<(isize, u8)>
+}
+
+impl StartCubes {
+	// update for the cube at pos changing from old_enc to new_enc,
+	//   where None is the encoding of a cube being added or removed
+	// only that cube can join or leave the start cubes, unless its
+	//   new value is larger than theirs, when it replaces them all
+	pub fn update(&mut self, pos: isize, old_enc: Option<isize>, new_enc: Option<isize>) {
+		// the best pair is dropped once its rotation no longer maximizes
+		//   its start cube's value, or its cube is no longer a start cube
+		//   (the start cubes only become unknown after it was dropped,
+		//   since that needs the last cube with its value to change)
+		if self.best.is_some_and(|(best_pos, rotations_index)| best_pos == pos
+				&& !new_enc.is_some_and(|enc| MAXIMUM_CUBE_ROTATION_INDICES[enc as usize].contains(&rotations_index))) {
+			self.best = None;
+		}
+		if !self.known {
+			return;
+		}
+		let was_start_cube = old_enc.is_some_and(|enc| MAXIMUM_ROTATED_CUBE_VALUES[enc as usize] == self.value);
+		match new_enc.map(|enc| MAXIMUM_ROTATED_CUBE_VALUES[enc as usize]) {
+			Some(value) if value > self.value => {
+				self.positions.clear();
+				self.positions.push(pos);
+				self.value = value;
+				if self.best.is_some_and(|(best_pos, _)| best_pos != pos) {
+					self.best = None;
+				}
+			}
+			Some(value) if value == self.value => {
+				if !was_start_cube {
+					if let Err(index) = self.positions.binary_search(&pos) {
+						self.positions.insert(index, pos);
+					}
+				}
+			}
+			_ => {
+				if was_start_cube {
+					if let Ok(index) = self.positions.binary_search(&pos) {
+						self.positions.remove(index);
+					}
+				}
+				if self.best.is_some_and(|(best_pos, _)| best_pos == pos) {
+					self.best = None;
+				}
+			}
+		}
+	}
+}
+
+// counters for how often parts of the search are used
+// each polycube being extended keeps its own counters (so updating
+//   them is cheap), which are added to its thread's after each job,
+//   and each thread adds its counters to SEARCH_STATS_TOTAL when it
+//   finishes
+#[derive(Clone, Copy, Default)]
+pub struct SearchStats {
+	// the number of P+A-B candidates compared against P
+	pub invariant_checks: u64,
+	// how many of those were ruled out by the cube value counts
+	//   alone, without a full canonical encoding of P+A-B
+	pub invariant_rejections: u64,
+	// the number of P+A skipped without being encoded, because one of
+	//   P's symmetries moves A to a position already tried
+	pub symmetric_duplicates: u64,
+	// the number of full canonical encodings computed
+	pub canonical_encodings: u64,
+	#[cfg(feature = "instrument")]
+	pub instrument: InstrumentCounters
+}
+
+impl SearchStats {
+	pub const fn new() -> SearchStats {
+		SearchStats {
+			invariant_checks: 0,
+			invariant_rejections: 0,
+			symmetric_duplicates: 0,
+			canonical_encodings: 0,
+			#[cfg(feature = "instrument")]
+			instrument: InstrumentCounters::new()
+		}
+	}
+
+	pub fn add(&mut self, other: &SearchStats) {
+		self.invariant_checks += other.invariant_checks;
+		self.invariant_rejections += other.invariant_rejections;
+		self.symmetric_duplicates += other.symmetric_duplicates;
+		self.canonical_encodings += other.canonical_encodings;
+		#[cfg(feature = "instrument")]
+		self.instrument.add(&other.instrument);
+	}
+
+	pub fn print(&self) {
+		if self.invariant_checks > 0 {
+			println!("invariant prefilter: ruled out {} of {} P+A-B checks ({:.2}%) without encoding",
+				self.invariant_rejections,
+				self.invariant_checks,
+				self.invariant_rejections as f64 * 100.0 / self.invariant_checks as f64);
+		}
+		println!("symmetric duplicates: skipped {} P+A without encoding", self.symmetric_duplicates);
+		println!("canonical encodings computed: {}", self.canonical_encodings);
+		#[cfg(feature = "instrument")]
+		self.instrument.print();
+	}
+
+	pub fn to_json(&self) -> serde_json::Value {
+		#[allow(unused_mut)]
+		let mut json = serde_json::json!({
+			"canonical_encodings": self.canonical_encodings,
+			"invariant_checks": self.invariant_checks,
+			"invariant_rejections": self.invariant_rejections,
+			"symmetric_duplicates": self.symmetric_duplicates
+		});
+		#[cfg(feature = "instrument")]
+		{
+			json["instrument"] = self.instrument.to_json();
+		}
+		json
+	}
+}
+
+thread_local! {
+	static SEARCH_STATS: RefCell<SearchStats> = const { RefCell::new(SearchStats::new()) };
+}
+
+static SEARCH_STATS_TOTAL: Mutex<SearchStats> = Mutex::new(SearchStats::new());
+
+// add this thread's counters to the totals, and reset them
+pub fn flush_search_stats() {
+	let stats = SEARCH_STATS.with_borrow_mut(std::mem::take);
+	SEARCH_STATS_TOTAL.lock().unwrap().add(&stats);
+}
+
+// return the totals flushed so far, and reset them
+pub fn take_search_stats_total() -> SearchStats {
+	std::mem::take(&mut *SEARCH_STATS_TOTAL.lock().unwrap())
+}
+
 pub struct ThreadResponse {
 	pub job_complete: bool,
 	pub results: Option<[usize; MAX_N]>,
-	pub orig_polycube: Option<Polycube>,
-	pub final_polycube_encs: Vec<u128>
+	// canonical encoding of the job, returned when the job was
+	//   not evaluated so it can be written to the resume file
+	pub orig_polycube_enc: Option<u128>,
+	pub final_polycube_encs: Vec<u128>,
+	// set when the job panicked on every attempt, to the panic
+	//   message, so the job can be quarantined instead of counted
+	pub failure: Option<String>,
+	// seconds spent evaluating a completed worker job
+	pub job_sec: Option<f64>,
+	// the features of a completed worker job, for projecting the final count
+	pub job_features: Option<JobFeatures>
 }
 pub struct CanonicalInfo {
 	// at 6 bits per cube, 128 bits is enough room for a polycube of
 	//   size 21, but polycubes have never been enumerated past n=18
 	//   so 128 bits is plenty long for now
 	enc: u128,
-	least_significant_cube_pos: isize,
-	max_cube_value: u8
+	// None until an encoding at least as large as the target is found
+	least_significant_cube_pos: Option<isize>,
+	max_cube_value: u8,
+	// the number of start cube and rotation pairs that give the
+	//   encoding, which is the number of rotations that leave the
+	//   polycube's shape unchanged (1 when it has no symmetry)
+	symmetry_order: u8,
+	// the first start cube position and ROTATIONS index that gave the
+	//   encoding
+	start: Option<(isize, u8)>
 }
 
 impl CanonicalInfo {
@@ -285,7 +756,9 @@ impl CanonicalInfo {
 		CanonicalInfo {
 			enc: self.enc,
 			least_significant_cube_pos: self.least_significant_cube_pos,
-			max_cube_value: self.max_cube_value
+			max_cube_value: self.max_cube_value,
+			symmetry_order: self.symmetry_order,
+			start: self.start
 		}
 	}
 }
@@ -297,7 +770,19 @@ pub struct Polycube {
 	// keys     - positions of cubes in this polycube
 	// vals 0-5 - neighbors of the cube at that position in DIRECTIONS order
 	// val  6   - neighbor encoding for the cube at that position
-	cube_info_by_pos: BTreeMap<isize, [Option<isize>; 7]>
+	cube_info_by_pos: BTreeMap<isize, [Option<isize>; 7]>,
+	// the number of cubes with each MAXIMUM_ROTATED_CUBE_VALUES value
+	// since those values don't depend on rotation, two polycubes
+	//   with different counts here can't have the same canonical
+	//   encoding, and the counts are cheap to keep updated in
+	//   add() and remove()
+	cube_value_counts: [u8; 64],
+	// kept up to date in add() and remove(), and saved before trying
+	//   to extend the polycube so that they can be restored after
+	start_cubes: StartCubes,
+	// counted while extending this polycube, and added to the
+	//   thread's counters by record_search_stats()
+	search_stats: SearchStats
 }
 
 impl Polycube {
@@ -307,14 +792,20 @@ impl Polycube {
 			Polycube {
 				n: 1,
 				canonical_info: None,
-				cube_info_by_pos: BTreeMap::from([(0, [None, None, None, None, None, None, Some(0)])])
+				cube_info_by_pos: BTreeMap::from([(ORIGIN_POS, [None, None, None, None, None, None, Some(0)])]),
+				cube_value_counts: initial_cube_value_counts(),
+				start_cubes: StartCubes { positions: vec![ORIGIN_POS], value: 0, known: true, best: None },
+				search_stats: SearchStats::new()
 			}
 		// intialize with no cubes
 		} else {
 			Polycube {
 				n: 0,
 				canonical_info: None,
-				cube_info_by_pos: BTreeMap::new()
+				cube_info_by_pos: BTreeMap::new(),
+				cube_value_counts: [0; 64],
+				start_cubes: StartCubes { positions: Vec::new(), value: 0, known: true, best: None },
+				search_stats: SearchStats::new()
 			}
 		}
 	}
@@ -337,7 +828,7 @@ impl Polycube {
 		// we have the most significant cube at the end of the
 		//   vec, so this recursive function will .pop() cubes
 		//   off the use them in order of most significant first
-		self.rebuild_from_encoding_recursive(&mut cube_encodings, 0);
+		self.rebuild_from_encoding_recursive(&mut cube_encodings, ORIGIN_POS);
 	}
 
 	pub fn rebuild_from_encoding_recursive(&mut self, cube_encodings: &mut Vec<u8>, cube_pos: isize) {
@@ -359,6 +850,150 @@ impl Polycube {
 		}
 	}
 
+	// build a polycube from the coordinates of its cubes, which must
+	//   be connected, distinct, and within MAX_COORDINATE of the origin
+	pub fn from_coordinates(coordinates: &[(isize, isize, isize)]) -> Result<Polycube, String> {
+		let mut positions: BTreeSet<isize> = BTreeSet::new();
+		for (x, y, z) in coordinates.iter() {
+			if x.abs() > MAX_COORDINATE || y.abs() > MAX_COORDINATE || z.abs() > MAX_COORDINATE {
+				return Err(format!("cube ({}, {}, {}) is farther than {} from the origin along an axis", x, y, z, MAX_COORDINATE));
+			}
+			if !positions.insert(pos_from_coordinates(*x, *y, *z)) {
+				return Err(format!("cube ({}, {}, {}) is given more than once", x, y, z));
+			}
+		}
+		if positions.len() > MAX_N - 1 {
+			return Err(format!("{} cubes is more than the limit of {}", positions.len(), MAX_N - 1));
+		}
+		let mut polycube = Polycube::new(false);
+		// add the cubes in an order where each is next to one already added
+		let mut to_add: Vec<isize> = positions.iter().take(1).cloned().collect();
+		while let Some(pos) = to_add.pop() {
+			if polycube.cube_info_by_pos.contains_key(&pos) {
+				continue;
+			}
+			polycube.add(pos);
+			for direction_cost in DIRECTION_COSTS {
+				if positions.contains(&(pos + direction_cost)) {
+					to_add.push(pos + direction_cost);
+				}
+			}
+		}
+		if (polycube.n as usize) < positions.len() {
+			return Err(String::from("cubes are not all connected"));
+		}
+		Ok(polycube)
+	}
+
+	// the coordinates of each cube, in position order
+	pub fn coordinates(&self) -> Vec<(isize, isize, isize)> {
+		self.cube_info_by_pos.keys().map(|pos| pos_to_coordinates(*pos)).collect()
+	}
+
+	// the indices of the ROTATIONS that leave the polycube's shape
+	//   unchanged (only the identity for a polycube with no symmetry)
+	pub fn symmetries(&self) -> Vec<usize> {
+		let coordinates = self.coordinates();
+		let shape = normalized_coordinates(coordinates.clone());
+		(0..ROTATIONS.len())
+			.filter(|i| normalized_coordinates(rotated_coordinates(&coordinates, &ROTATIONS[*i])) == shape)
+			.collect()
+	}
+
+	// the moves for each of the polycube's symmetries other than the
+	//   identity, which put it back where it was
+	// adding a cube at a position gives the same polycube as adding
+	//   it where any of these moves put that position
+	pub fn symmetry_moves(&self) -> Vec<SymmetryMove> {
+		let coordinates = self.coordinates();
+		let min = minimum_coordinates(&coordinates);
+		self.symmetries().into_iter().filter(|i| *i != 0).map(|i| {
+			let rotated_min = minimum_coordinates(&rotated_coordinates(&coordinates, &ROTATIONS[i]));
+			SymmetryMove {
+				rotation: ROTATIONS[i],
+				offset: (min.0 - rotated_min.0, min.1 - rotated_min.1, min.2 - rotated_min.2)
+			}
+		}).collect()
+	}
+
+	// the number of the 24 rotations that leave the polycube's
+	//   shape unchanged (1 for a polycube with no symmetry)
+	pub fn symmetry_order(&self) -> u8 {
+		self.symmetries().len() as u8
+	}
+
+	// the polycube reflected through the x axis, which is the same
+	//   shape (after rotating) only if the polycube isn't chiral
+	pub fn mirrored(&self) -> Polycube {
+		let reflected: Vec<(isize, isize, isize)> = self.coordinates().iter().map(|(x, y, z)| (-x, *y, *z)).collect();
+		Polycube::from_coordinates(&normalized_coordinates(reflected)).unwrap()
+	}
+
+	// the number of the cubes' faces that aren't against another cube
+	pub fn face_count(&self) -> usize {
+		let adjacent_pairs: usize = self.cube_info_by_pos.keys()
+			.map(|pos| [X_STEP, Y_STEP, Z_STEP].iter().filter(|step| self.cube_info_by_pos.contains_key(&(pos + *step))).count())
+			.sum();
+		6 * self.n as usize - 2 * adjacent_pairs
+	}
+
+	// the dimensions of the polycube's bounding box, largest first
+	pub fn bounding_box(&self) -> [u8; 3] {
+		let coordinates = self.coordinates();
+		let extent = |axis: fn(&(isize, isize, isize)) -> isize| -> u8 {
+			let max = coordinates.iter().map(axis).max().unwrap_or(0);
+			let min = coordinates.iter().map(axis).min().unwrap_or(0);
+			if coordinates.is_empty() { 0 } else { (max - min + 1) as u8 }
+		};
+		let mut dimensions = [extent(|c| c.0), extent(|c| c.1), extent(|c| c.2)];
+		dimensions.sort_unstable_by(|a, b| b.cmp(a));
+		dimensions
+	}
+
+	// the polycube's parent in the canonical extension tree, which is
+	//   the polycube without its least significant cube (the monocube
+	//   has no parent)
+	pub fn parent(&mut self) -> Option<Polycube> {
+		if self.n <= 1 {
+			return None;
+		}
+		let least_significant_cube_pos = self.find_canonical_info(None).least_significant_cube_pos.unwrap();
+		let mut parent = self.copy();
+		parent.remove(least_significant_cube_pos);
+		Some(parent)
+	}
+
+	// the polycube's children in the canonical extension tree, in
+	//   increasing order of their canonical encodings
+	pub fn children(&mut self) -> Vec<Polycube> {
+		sorted_canonical_children(self).into_iter().map(|child_enc| {
+			let mut child = Polycube::new(false);
+			child.rebuild_from_encoding(child_enc);
+			child
+		}).collect()
+	}
+
+	// the polycube's ancestors in the canonical extension tree, from
+	//   its parent back to the monocube
+	pub fn ancestry(&mut self) -> Vec<Polycube> {
+		let mut ancestry: Vec<Polycube> = Vec::new();
+		let mut parent = self.parent();
+		while let Some(mut ancestor) = parent {
+			parent = ancestor.parent();
+			ancestry.push(ancestor);
+		}
+		ancestry
+	}
+
+	// panic if a cube at pos would be outside the coordinate bounds,
+	//   since its position would then be mistaken for another
+	pub fn check_bounds(&self, pos: isize) {
+		if !pos_in_bounds(pos) {
+			let (x, y, z) = pos_to_coordinates(pos);
+			panic!("cannot add a cube at ({}, {}, {}), which is farther than {} from the origin along an axis", x, y, z, MAX_COORDINATE);
+		}
+	}
+
 	pub fn copy(&self) -> Polycube {
 		match self.canonical_info {
 			Some(ref canonical_info) => {
@@ -366,17 +1001,25 @@ impl Polycube {
 					n: self.n,
 					canonical_info: Some(CanonicalInfo {
 						enc: canonical_info.enc,
-						least_significant_cube_pos: canonical_info.least_significant_cube_pos.clone(),
-						max_cube_value: canonical_info.max_cube_value
+						least_significant_cube_pos: canonical_info.least_significant_cube_pos,
+						max_cube_value: canonical_info.max_cube_value,
+						symmetry_order: canonical_info.symmetry_order,
+						start: canonical_info.start
 					}),
-					cube_info_by_pos: self.cube_info_by_pos.clone()
+					cube_info_by_pos: self.cube_info_by_pos.clone(),
+					cube_value_counts: self.cube_value_counts,
+					start_cubes: self.start_cubes.clone(),
+					search_stats: SearchStats::new()
 				}
 			}
 			None => {
 				Polycube {
 					n: self.n,
 					canonical_info: None,
-					cube_info_by_pos: self.cube_info_by_pos.clone()
+					cube_info_by_pos: self.cube_info_by_pos.clone(),
+					cube_value_counts: self.cube_value_counts,
+					start_cubes: self.start_cubes.clone(),
+					search_stats: SearchStats::new()
 				}
 			}
 		}
@@ -384,6 +1027,7 @@ impl Polycube {
 
 	// same as add_loop() below but with the loop unrolled
 	pub fn add(&mut self, pos: isize) {
+		self.check_bounds(pos);
 		let mut new_enc: isize = 0;
 		let mut new_info: [Option<isize>; 7] = [None, None, None, None, None, None, Some(0)];
 
@@ -391,68 +1035,80 @@ impl Polycube {
 		//   rotation of [0,1,2,3,4,5]
 		// set the neighbors for the new cube and set it as a neighbor to those cubes
 
-		// direction = 0 -> direction cost = -1
-		let mut neighbor_pos = pos - 1;
+		// direction = 0 -> direction cost = -X_STEP
+		let mut neighbor_pos = pos - X_STEP;
 		match self.cube_info_by_pos.get_mut(&neighbor_pos) {
 			Some(neighbor_info) => {
 				new_info[0] = Some(neighbor_pos);
 				new_enc |= 32;
 				neighbor_info[1] = Some(pos);
+				update_cube_value_counts(&mut self.cube_value_counts, neighbor_info[6].unwrap(), neighbor_info[6].unwrap() | 16);
+				self.start_cubes.update(neighbor_pos, Some(neighbor_info[6].unwrap()), Some(neighbor_info[6].unwrap() | 16));
 				neighbor_info[6] = Some(neighbor_info[6].unwrap() | 16);
 			}
 			None => {}
 		}
-		// direction = 1 -> direction cost = 1
-		neighbor_pos = pos + 1;
+		// direction = 1 -> direction cost = X_STEP
+		neighbor_pos = pos + X_STEP;
 		match self.cube_info_by_pos.get_mut(&neighbor_pos) {
 			Some(neighbor_info) => {
 				new_info[1] = Some(neighbor_pos);
 				new_enc |= 16;
 				neighbor_info[0] = Some(pos);
+				update_cube_value_counts(&mut self.cube_value_counts, neighbor_info[6].unwrap(), neighbor_info[6].unwrap() | 32);
+				self.start_cubes.update(neighbor_pos, Some(neighbor_info[6].unwrap()), Some(neighbor_info[6].unwrap() | 32));
 				neighbor_info[6] = Some(neighbor_info[6].unwrap() | 32);
 			}
 			None => {}
 		}
-		// direction = 2 -> direction cost = -100
-		neighbor_pos = pos - 100;
+		// direction = 2 -> direction cost = -Y_STEP
+		neighbor_pos = pos - Y_STEP;
 		match self.cube_info_by_pos.get_mut(&neighbor_pos) {
 			Some(neighbor_info) => {
 				new_info[2] = Some(neighbor_pos);
 				new_enc |= 8;
 				neighbor_info[3] = Some(pos);
+				update_cube_value_counts(&mut self.cube_value_counts, neighbor_info[6].unwrap(), neighbor_info[6].unwrap() | 4);
+				self.start_cubes.update(neighbor_pos, Some(neighbor_info[6].unwrap()), Some(neighbor_info[6].unwrap() | 4));
 				neighbor_info[6] = Some(neighbor_info[6].unwrap() | 4);
 			}
 			None => {}
 		}
-		// direction = 3 -> direction cost = 100
-		neighbor_pos = pos + 100;
+		// direction = 3 -> direction cost = Y_STEP
+		neighbor_pos = pos + Y_STEP;
 		match self.cube_info_by_pos.get_mut(&neighbor_pos) {
 			Some(neighbor_info) => {
 				new_info[3] = Some(neighbor_pos);
 				new_enc |= 4;
 				neighbor_info[2] = Some(pos);
+				update_cube_value_counts(&mut self.cube_value_counts, neighbor_info[6].unwrap(), neighbor_info[6].unwrap() | 8);
+				self.start_cubes.update(neighbor_pos, Some(neighbor_info[6].unwrap()), Some(neighbor_info[6].unwrap() | 8));
 				neighbor_info[6] = Some(neighbor_info[6].unwrap() | 8);
 			}
 			None => {}
 		}
-		// direction = 4 -> direction cost = -10000
-		neighbor_pos = pos - 10000;
+		// direction = 4 -> direction cost = -Z_STEP
+		neighbor_pos = pos - Z_STEP;
 		match self.cube_info_by_pos.get_mut(&neighbor_pos) {
 			Some(neighbor_info) => {
 				new_info[4] = Some(neighbor_pos);
 				new_enc |= 2;
 				neighbor_info[5] = Some(pos);
+				update_cube_value_counts(&mut self.cube_value_counts, neighbor_info[6].unwrap(), neighbor_info[6].unwrap() | 1);
+				self.start_cubes.update(neighbor_pos, Some(neighbor_info[6].unwrap()), Some(neighbor_info[6].unwrap() | 1));
 				neighbor_info[6] = Some(neighbor_info[6].unwrap() | 1);
 			}
 			None => {}
 		}
-		// direction = 5 -> direction cost = 10000
-		neighbor_pos = pos + 10000;
+		// direction = 5 -> direction cost = Z_STEP
+		neighbor_pos = pos + Z_STEP;
 		match self.cube_info_by_pos.get_mut(&neighbor_pos) {
 			Some(neighbor_info) => {
 				new_info[5] = Some(neighbor_pos);
 				new_enc |= 1;
 				neighbor_info[4] = Some(pos);
+				update_cube_value_counts(&mut self.cube_value_counts, neighbor_info[6].unwrap(), neighbor_info[6].unwrap() | 2);
+				self.start_cubes.update(neighbor_pos, Some(neighbor_info[6].unwrap()), Some(neighbor_info[6].unwrap() | 2));
 				neighbor_info[6] = Some(neighbor_info[6].unwrap() | 2);
 			}
 			None => {}
@@ -460,12 +1116,15 @@ impl Polycube {
 		// lastly, insert the new cube's encoded neighbors into our map
 		new_info[6] = Some(new_enc);
 		self.cube_info_by_pos.insert(pos, new_info);
+		self.cube_value_counts[MAXIMUM_ROTATED_CUBE_VALUES[new_enc as usize] as usize] += 1;
 		self.n += 1;
 		self.canonical_info = None;
+		self.start_cubes.update(pos, None, Some(new_enc));
 	}
 
 	// this is the original loop that was unrolled above in add()
 	pub fn add_loop(&mut self, pos: isize) {
+		self.check_bounds(pos);
 		let mut new_enc: isize = 0;
 		let mut new_info: [Option<isize>; 7] = [None, None, None, None, None, None, Some(0)];
 
@@ -493,7 +1152,10 @@ impl Polycube {
 					//   in each cube's .enc value, so we need '0' to
 					//   cause a left shift by 5 bits (and here we use
 					//   XOR to flip to the opposite direction)
-					neighbor_info[6] = Some(neighbor_info[6].unwrap() | (1 << ((5-direction) ^ 1)));
+					let neighbor_enc = neighbor_info[6].unwrap() | (1 << ((5-direction) ^ 1));
+					update_cube_value_counts(&mut self.cube_value_counts, neighbor_info[6].unwrap(), neighbor_enc);
+					self.start_cubes.update(neighbor_pos, Some(neighbor_info[6].unwrap()), Some(neighbor_enc));
+					neighbor_info[6] = Some(neighbor_enc);
 				}
 				None => {}
 			}
@@ -501,13 +1163,16 @@ impl Polycube {
 		// lastly, insert the new cube's encoded neighbors into our map
 		new_info[6] = Some(new_enc);
 		self.cube_info_by_pos.insert(pos, new_info);
+		self.cube_value_counts[MAXIMUM_ROTATED_CUBE_VALUES[new_enc as usize] as usize] += 1;
 		self.n += 1;
 		self.canonical_info = None;
+		self.start_cubes.update(pos, None, Some(new_enc));
 	}
 
 	pub fn remove(&mut self, pos: isize) {
 		// first remove the cube's data from our map
 		let cube_info = self.cube_info_by_pos.remove(&pos).unwrap();
+		self.cube_value_counts[MAXIMUM_ROTATED_CUBE_VALUES[cube_info[6].unwrap() as usize] as usize] -= 1;
 		// remove this cube from each of its neighbors
 		for (dir, neighbor_pos) in (&cube_info[0..6]).iter().enumerate() {
 			match neighbor_pos {
@@ -519,7 +1184,10 @@ impl Polycube {
 							//   in each cube's .enc value, so we need '0' to
 							//   cause a left shift by 5 bits (and here we use
 							//   XOR to flip to the opposite direction)
-							neighbor_info[6] = Some(neighbor_info[6].unwrap() - (1 << ((5-dir) ^ 1)));
+							let neighbor_enc = neighbor_info[6].unwrap() - (1 << ((5-dir) ^ 1));
+							update_cube_value_counts(&mut self.cube_value_counts, neighbor_info[6].unwrap(), neighbor_enc);
+							self.start_cubes.update(*neighbor_pos, Some(neighbor_info[6].unwrap()), Some(neighbor_enc));
+							neighbor_info[6] = Some(neighbor_enc);
 							// use XOR to flip between each direction and its opposite
 							//   to set the neighbor's neighbor to None
 							//   (0<->1, 2<->3, 4<->5)
@@ -535,6 +1203,69 @@ impl Polycube {
 		}
 		self.n -= 1;
 		self.canonical_info = None;
+		self.start_cubes.update(pos, cube_info[6], None);
+		if self.cube_value_counts[self.start_cubes.value as usize] == 0 {
+			self.start_cubes.known = false;
+		}
+	}
+
+	// find the start cubes by looking at every cube
+	pub fn find_start_cubes(&self) -> Vec<isize> {
+		let max_cube_value = match self.find_maximum_cube_value_if_any() {
+			Some(max_cube_value) => max_cube_value,
+			None => return Vec::new()
+		};
+		self.cube_info_by_pos.iter()
+			.filter(|(_, cube_info)| MAXIMUM_ROTATED_CUBE_VALUES[cube_info[6].unwrap() as usize] == max_cube_value)
+			.map(|(cube_pos, _)| *cube_pos)
+			.collect()
+	}
+
+	// find the start cubes again if removing a cube made them unknown
+	pub fn refresh_start_cubes(&mut self) {
+		if !self.start_cubes.known {
+			self.start_cubes.positions = self.find_start_cubes();
+			self.start_cubes.value = self.find_maximum_cube_value_if_any().unwrap_or(0);
+			self.start_cubes.known = true;
+			let positions = &self.start_cubes.positions;
+			self.start_cubes.best = self.start_cubes.best.filter(|(best_pos, _)| positions.binary_search(best_pos).is_ok());
+		}
+	}
+
+	// the start cubes, found from scratch if they aren't known
+	pub fn start_cube_positions(&self) -> Cow<'_, [isize]> {
+		if self.start_cubes.known {
+			Cow::Borrowed(self.start_cubes.positions.as_slice())
+		} else {
+			Cow::Owned(self.find_start_cubes())
+		}
+	}
+
+	// the start cube and rotation pairs that the canonical encoding
+	//   can start from, beginning with the best pair kept from the
+	//   last canonical encoding (if any), which isn't given again
+	pub fn start_pairs(&self) -> impl Iterator<Item = (isize, u8)> + '_ {
+		let best = self.start_cubes.best;
+		let positions = self.start_cube_positions();
+		best.into_iter().chain((0..positions.len()).flat_map(move |i| {
+			let cube_pos = positions[i];
+			let cube_enc = self.cube_info_by_pos[&cube_pos][6].unwrap() as usize;
+			MAXIMUM_CUBE_ROTATION_INDICES[cube_enc].iter()
+				.map(move |rotations_index| (cube_pos, *rotations_index))
+				.filter(move |pair| Some(*pair) != best)
+		}))
+	}
+
+	// the start cubes, saved before extending this polycube so that
+	//   they can be put back with restore_start_cubes() after each
+	//   added cube is removed again, instead of finding them again
+	pub fn save_start_cubes(&mut self) -> StartCubes {
+		self.refresh_start_cubes();
+		self.start_cubes.clone()
+	}
+
+	pub fn restore_start_cubes(&mut self, saved: &StartCubes) {
+		self.start_cubes.clone_from(saved);
 	}
 
 	// for each cube, find its maximum value after a would-be rotation,
@@ -558,8 +1289,56 @@ impl Polycube {
 	//   a start_cube+rotation that results in the largest possible cube_enc
 	//   value, we only need to find what that single largest value is
 	// (so we'll use this function instead of the above find_maximum_cube_values())
+	// (the cube value counts give us this without looking at every cube)
 	pub fn find_maximum_cube_value(&self) -> u8 {
-		return self.cube_info_by_pos.values().map(|info| MAXIMUM_ROTATED_CUBE_VALUES[info[6].unwrap() as usize]).max().unwrap();
+		self.find_maximum_cube_value_if_any().unwrap()
+	}
+
+	// same as above, but allowing for a polycube with no cubes
+	pub fn find_maximum_cube_value_if_any(&self) -> Option<u8> {
+		(0..64u8).rev().find(|value| self.cube_value_counts[*value as usize] > 0)
+	}
+
+	// a cheap test that must pass for this polycube to have the same
+	//   canonical encoding as the polycube whose cube value counts
+	//   are given
+	// this also keeps track of how often the test rules out the
+	//   full encoding pass that would otherwise be needed
+	pub fn matches_invariants(&mut self, other_cube_value_counts: &[u8; 64]) -> bool {
+		let matches = self.cube_value_counts == *other_cube_value_counts;
+		self.search_stats.invariant_checks += 1;
+		self.search_stats.invariant_rejections += !matches as u64;
+		matches
+	}
+
+	// the moves for the polycube's symmetries, which are only looked
+	//   for when its canonical encoding was found from more than one
+	//   start cube and rotation (so most polycubes skip this)
+	pub fn find_symmetry_moves(&mut self) -> Vec<SymmetryMove> {
+		if self.find_canonical_info(None).symmetry_order > 1 {
+			self.symmetry_moves()
+		} else {
+			Vec::new()
+		}
+	}
+
+	// mark the positions that the symmetry moves put pos at as tried,
+	//   since adding a cube there gives the same polycube as adding
+	//   one at pos, so it would only be encoded to be found in
+	//   tried_canonicals and skipped
+	pub fn skip_symmetric_positions(&mut self, symmetry_moves: &[SymmetryMove], pos: isize, tried_pos: &mut BTreeSet<isize>) {
+		for symmetry_move in symmetry_moves.iter() {
+			if tried_pos.insert(symmetry_move.moved(pos)) {
+				self.search_stats.symmetric_duplicates += 1;
+			}
+		}
+	}
+
+	// add the counters kept while extending this polycube to this
+	//   thread's, and reset them
+	pub fn record_search_stats(&mut self) {
+		let stats = std::mem::take(&mut self.search_stats);
+		SEARCH_STATS.with_borrow_mut(|thread_stats| thread_stats.add(&stats));
 	}
 
 	// same as make_encoding_recursive_loop(), but we've
@@ -579,6 +1358,7 @@ impl Polycube {
 		//   (smaller int value than the given best known encofing)
 		//   we can stop right away
 		if encoding < (best_encoding >> (offset * 6)) {
+			instrument!(c => c.abandoned_at_depth[(self.n - offset) as usize] += 1);
 			return None;
 		}
 		let mut least_sig_cube_pos = start_cube_pos;
@@ -784,9 +1564,11 @@ impl Polycube {
 			//   (smaller int value than the given best known encofing)
 			//   we can stop right away
 			if encoding < (best_encoding >> (offset * 6)) {
+				instrument!(c => c.abandoned_at_depth[(self.n - offset) as usize] += 1);
 				return None;
 			}
-			offset -= 1;
+			// the last cube has an offset of 0, so don't go below that
+			offset = offset.saturating_sub(1);
 			least_sig_cube_pos = cube_pos;
 
 			// reverse the iterator here so that directions are
@@ -837,6 +1619,7 @@ impl Polycube {
 		//   (smaller int value than the given best known encofing)
 		//   we can stop right away
 		if encoding < (best_encoding >> (offset * 6)) {
+			instrument!(c => c.abandoned_at_depth[(self.n - offset) as usize] += 1);
 			return None;
 		}
 		let mut least_sig_cube_pos = start_cube_pos;
@@ -847,7 +1630,7 @@ impl Polycube {
 					if included_cube_pos.contains(&neighbor_pos) {
 						continue;
 					}
-					match self.make_encoding_recursive(
+					match self.make_encoding_recursive_loop(
 							neighbor_pos,
 							rotation,
 							included_cube_pos,
@@ -875,209 +1658,947 @@ impl Polycube {
 		return Some((least_sig_cube_pos, encoding, offset));
 	}
 
+	// encode the polycube from the given start cube and rotation,
+	//   using whichever Canonicalizer has been selected
 	pub fn make_encoding(&self, start_cube_pos: isize, rotations_index: usize, best_encoding: u128) -> Option<(u128, isize)> {
-
-//		// uses an iterative depth-first encoding of all cubes, using
-//		//   the provided rotation's order to traverse the cubes
-//		match self.make_encoding_iterative(
-//				start_cube_pos,
-//				best_encoding,
-//				rotations_index) {
-//			Some((least_sig_cube_pos, encoding, _offset)) => {
-//				return Some((encoding, least_sig_cube_pos));
-//			}
-//			// if the Option is empty, that means we have determined
-//			//   somewhere deeper in the recursion that this is
-//			//   a dead-end inferior encoding, so we can stop
-//			None => {
-//				return None;
-//			}
-//		}
-
-		// uses a recursive depth-first encoding of all cubes, using
-		//   the provided rotation's order to traverse the cubes
-		let mut included_cube_pos: BTreeSet<isize> = BTreeSet::new();
-		match self.make_encoding_recursive(
-				start_cube_pos,
-				ROTATIONS[rotations_index],
-				&mut included_cube_pos,
-				best_encoding,
-				rotations_index,
-				self.n - 1, // number of 6-bit shifts from the right, where the last cube has an offset of 0
-				0) {
-			Some((least_sig_cube_pos, encoding, _offset)) => {
-				return Some((encoding, least_sig_cube_pos));
-			}
-			// if the Option is empty, that means we have determined
-			//   somewhere deeper in the recursion that this is
-			//   a dead-end inferior encoding, so we can stop
-			None => {
-				return None;
-			}
-		}
+		canonicalizer().make_encoding(self, start_cube_pos, rotations_index, best_encoding)
 	}
 
 	// return our canonical info, calculating it first if necessary
-	pub fn find_canonical_info(&mut self, look_for_pos_as_least_significant: isize) -> &CanonicalInfo {
+	pub fn find_canonical_info(&mut self, look_for_pos_as_least_significant: Option<isize>) -> &CanonicalInfo {
 		return self.find_canonical_info_with_target(look_for_pos_as_least_significant, 0);
 	}
 
-	pub fn find_canonical_info_with_target(&mut self, look_for_pos_as_least_significant: isize, target_encoding: u128) -> &CanonicalInfo {
+	pub fn find_canonical_info_with_target(&mut self, look_for_pos_as_least_significant: Option<isize>, target_encoding: u128) -> &CanonicalInfo {
 		if self.canonical_info.is_none() {
-			let mut canonical = CanonicalInfo {
-				enc: 0,
-				least_significant_cube_pos: IMPOSSIBLE_POS,
-				max_cube_value: self.find_maximum_cube_value()
-			};
-			let mut best_encoding: u128 = target_encoding;
-			let mut encoding_diff: u128;
-			for (cube_pos, cube_info) in self.cube_info_by_pos.iter() {
-				let cube_enc = cube_info[6].unwrap() as usize;
-				// there could be more than one cube with the maximum rotated value
-				if MAXIMUM_ROTATED_CUBE_VALUES[cube_enc] < canonical.max_cube_value {
-					continue;
-				}
-				for rotations_index in MAXIMUM_CUBE_ROTATION_INDICES[cube_enc].iter() {
-					match self.make_encoding(*cube_pos, *rotations_index as usize, best_encoding) {
-						Some((encoding, least_significant_cube_pos)) => {
-							encoding_diff = encoding - best_encoding;
-							if encoding_diff > 0 {
-								canonical.enc = encoding;
-								canonical.least_significant_cube_pos = least_significant_cube_pos;
-								best_encoding = encoding;
-							// if we've found an equivalent encoding but where the
-							//   tracked cube ends up in the least significant position,
-							//   record the fact of that
-							} else if encoding_diff == 0 && least_significant_cube_pos == look_for_pos_as_least_significant {
-								canonical.least_significant_cube_pos = least_significant_cube_pos;
-							}
-						}
-						// if the Option is empty, that means we have determined
-						//   somewhere in the recursion that this is a dead-end
-						//   inferior encoding, so we can try the next rotation
-						None => {
-							continue;
-						}
-					}
-				}
-			}
-			self.canonical_info = Some(canonical);
+			self.search_stats.canonical_encodings += 1;
+			self.refresh_start_cubes();
+			let canonical_info = canonicalizer().find_canonical_info(self, look_for_pos_as_least_significant, target_encoding);
+			self.set_canonical_info(canonical_info);
 		}
 		return self.canonical_info.as_ref().unwrap();
 	}
 
+	// keep the canonical info, and the start cube and rotation that gave
+	//   it, which are tried first when encoding the polycube again after
+	//   cubes are added or removed
+	pub fn set_canonical_info(&mut self, canonical_info: CanonicalInfo) {
+		if canonical_info.start.is_some() && REUSE_BEST_START.load(Ordering::Relaxed) {
+			self.start_cubes.best = canonical_info.start;
+		}
+		self.canonical_info = Some(canonical_info);
+	}
+
 	// when we are looking for an encoding at least as large as the target,
 	//   we can ignore all smaller encodings
 	// this DOES NOT set the actual encoding for the polycube if smaller
 	//   than the target, so it's only useful e.g. when checking if P+A-B=P
 	pub fn find_canonical_enc_with_target(&mut self, target_encoding: u128) -> u128 {
-		// leave default enc as 0 so we fail the P+A-B=P check if we
-		//   don't find an encoding at least as large as the target
+		self.search_stats.canonical_encodings += 1;
+		self.refresh_start_cubes();
+		canonicalizer().find_canonical_enc(self, target_encoding)
+	}
+}
+
+// a way of finding the encoding of a polycube for a given start
+//   cube and rotation, which is what all the time is spent on
+// the implementations here all give the same encodings, and one
+//   can be chosen at runtime (with --canonicalizer) to compare
+//   their speed
+pub trait Canonicalizer: Sync {
+	// the name used to select this canonicalizer
+	fn name(&self) -> &'static str;
+
+	// returns the encoding and the position of the least significant
+	//   cube, or None as soon as we can tell the encoding will be
+	//   less than best_encoding
+	fn make_encoding(&self, polycube: &Polycube, start_cube_pos: isize, rotations_index: usize, best_encoding: u128) -> Option<(u128, isize)>;
+
+	// find the maximal "canonical" encoding, if it is larger than the target
+	fn find_canonical_info(&self, polycube: &Polycube, look_for_pos_as_least_significant: Option<isize>, target_encoding: u128) -> CanonicalInfo {
 		let mut canonical = CanonicalInfo {
 			enc: 0,
-			least_significant_cube_pos: IMPOSSIBLE_POS,
-			max_cube_value: self.find_maximum_cube_value()
+			least_significant_cube_pos: None,
+			max_cube_value: polycube.find_maximum_cube_value(),
+			symmetry_order: 0,
+			start: None
 		};
 		let mut best_encoding: u128 = target_encoding;
-		for (cube_pos, cube_info) in self.cube_info_by_pos.iter() {
-			let cube_enc = cube_info[6].unwrap() as usize;
-			// there could be more than one cube with the maximum rotated value
-			if MAXIMUM_ROTATED_CUBE_VALUES[cube_enc] < canonical.max_cube_value {
-				continue;
-			}
-			for rotations_index in MAXIMUM_CUBE_ROTATION_INDICES[cube_enc].iter() {
-				match self.make_encoding(*cube_pos, *rotations_index as usize, best_encoding) {
-					Some((encoding, least_significant_cube_pos)) => {
-						if encoding >= best_encoding {
-							canonical.enc = encoding;
-							canonical.least_significant_cube_pos = least_significant_cube_pos;
-							best_encoding = encoding;
+		let mut encoding_diff: u128;
+		// there could be more than one cube with the maximum rotated value
+		for (cube_pos, rotations_index) in polycube.start_pairs() {
+			instrument!(c => c.make_encoding_calls += 1);
+			match self.make_encoding(polycube, cube_pos, rotations_index as usize, best_encoding) {
+				Some((encoding, least_significant_cube_pos)) => {
+					encoding_diff = encoding - best_encoding;
+					if encoding_diff > 0 {
+						canonical.enc = encoding;
+						canonical.least_significant_cube_pos = Some(least_significant_cube_pos);
+						canonical.symmetry_order = 1;
+						canonical.start = Some((cube_pos, rotations_index));
+						best_encoding = encoding;
+					} else if encoding_diff == 0 {
+						canonical.symmetry_order += 1;
+						// if we've found an equivalent encoding but where the
+						//   tracked cube ends up in the least significant position,
+						//   record the fact of that
+						if Some(least_significant_cube_pos) == look_for_pos_as_least_significant {
+							canonical.least_significant_cube_pos = Some(least_significant_cube_pos);
 						}
 					}
-					// if the Option is empty, that means we have determined
-					//   somewhere in the recursion that this is a dead-end
-					//   inferior encoding, so we can try the next rotation
-					None => {
-						continue;
-					}
+				}
+				// if the Option is empty, that means we have determined
+				//   somewhere in the recursion that this is a dead-end
+				//   inferior encoding, so we can try the next rotation
+				None => {
+					continue;
 				}
 			}
 		}
-		return canonical.enc;
+		canonical
 	}
-}
 
-static mut N_COUNTS: [usize; 23] = [0; 23];
-
-
-//  the initial delegator worker begins here
-pub fn extend_and_delegate_outer(polycube: &mut Polycube, n: u8, atomic_halt: Arc<AtomicBool>,
-		submit_queue: Arc<ArrayQueue<Polycube>>, response_queue: Arc<ArrayQueue<ThreadResponse>>, spawn_n: u8) {
-	// thread-local random generator
-	let mut rng = thread_rng();
-	match extend_and_delegate(
-			polycube,
-			n,
-			spawn_n,
-			&submit_queue,
-			&response_queue,
-			&atomic_halt,
-			&mut rng) {
-		Some(found_counts_by_n) => {
-			let mut all_n_counts: [usize; MAX_N] = [0; MAX_N];
-			for i in 1..n+1 {
-				match found_counts_by_n.get(i as usize) {
-					Some(count) => {
-						all_n_counts[i as usize] = *count;
-					},
-					None => {}
-				}
-			}
-			match response_queue.push(ThreadResponse{
-					job_complete: true,
-					results: Some(all_n_counts),
-					orig_polycube: None,
-					final_polycube_encs: Vec::new() }) {
-				Ok(_) => {}
-				Err(_) => {
-					panic!("response_queue.push() failed");
+	// when we are looking for an encoding at least as large as the target,
+	//   we can ignore all smaller encodings
+	// this DOES NOT find the actual encoding for the polycube if smaller
+	//   than the target, so it's only useful e.g. when checking if P+A-B=P
+	fn find_canonical_enc(&self, polycube: &Polycube, target_encoding: u128) -> u128 {
+		// leave default enc as 0 so we fail the P+A-B=P check if we
+		//   don't find an encoding at least as large as the target
+		let mut canonical = CanonicalInfo {
+			enc: 0,
+			least_significant_cube_pos: None,
+			max_cube_value: polycube.find_maximum_cube_value(),
+			symmetry_order: 0,
+			start: None
+		};
+		let mut best_encoding: u128 = target_encoding;
+		// there could be more than one cube with the maximum rotated value
+		for (cube_pos, rotations_index) in polycube.start_pairs() {
+			instrument!(c => c.make_encoding_calls += 1);
+			match self.make_encoding(polycube, cube_pos, rotations_index as usize, best_encoding) {
+				Some((encoding, least_significant_cube_pos)) => {
+					if encoding >= best_encoding {
+						canonical.enc = encoding;
+						canonical.least_significant_cube_pos = Some(least_significant_cube_pos);
+						best_encoding = encoding;
+					}
 				}
-			}
-		}
-		None => {
-			// indicate that the initial delegator worker was
-			//   halted with a special-case results=None and polycube=None here
-			match response_queue.push(ThreadResponse{
-					job_complete: false,
-					results: None,
-					orig_polycube: None,
-					final_polycube_encs: Vec::new() }) {
-				Ok(_) => {}
-				Err(_) => {
-					panic!("response_queue.push() failed");
+				// if the Option is empty, that means we have determined
+				//   somewhere in the recursion that this is a dead-end
+				//   inferior encoding, so we can try the next rotation
+				None => {
+					continue;
 				}
 			}
 		}
+		canonical.enc
 	}
 }
 
-pub fn extend_as_worker_outer(
-		n: u8,
-		atomic_halt: Arc<AtomicBool>,
-		atomic_done: Arc<AtomicBool>,
-		atomic_waiting: Arc<AtomicBool>,
-		submit_queue: Arc<ArrayQueue<Polycube>>,
-		response_queue: Arc<ArrayQueue<ThreadResponse>>,
-		save_final_polycubes: bool) {
-	let mut halted = false;
-	// thread-local random generator
-	let mut rng = thread_rng();
-	// by default, we are not signalling that this worker
+// uses make_encoding_recursive(), a recursive depth-first encoding
+//   of all cubes with its loop over the directions unrolled
+pub struct RecursiveCanonicalizer;
+
+impl Canonicalizer for RecursiveCanonicalizer {
+	fn name(&self) -> &'static str {
+		"recursive"
+	}
+
+	fn make_encoding(&self, polycube: &Polycube, start_cube_pos: isize, rotations_index: usize, best_encoding: u128) -> Option<(u128, isize)> {
+		// uses a recursive depth-first encoding of all cubes, using
+		//   the provided rotation's order to traverse the cubes
+		let mut included_cube_pos: BTreeSet<isize> = BTreeSet::new();
+		polycube.make_encoding_recursive(
+				start_cube_pos,
+				ROTATIONS[rotations_index],
+				&mut included_cube_pos,
+				best_encoding,
+				rotations_index,
+				polycube.n - 1, // number of 6-bit shifts from the right, where the last cube has an offset of 0
+				0)
+			// if the Option is empty, that means we have determined
+			//   somewhere deeper in the recursion that this is
+			//   a dead-end inferior encoding, so we can stop
+			.map(|(least_sig_cube_pos, encoding, _offset)| (encoding, least_sig_cube_pos))
+	}
+}
+
+// uses make_encoding_recursive_loop(), the original version of
+//   make_encoding_recursive() before its loop was unrolled
+pub struct RecursiveLoopCanonicalizer;
+
+impl Canonicalizer for RecursiveLoopCanonicalizer {
+	fn name(&self) -> &'static str {
+		"recursive-loop"
+	}
+
+	fn make_encoding(&self, polycube: &Polycube, start_cube_pos: isize, rotations_index: usize, best_encoding: u128) -> Option<(u128, isize)> {
+		let mut included_cube_pos: BTreeSet<isize> = BTreeSet::new();
+		polycube.make_encoding_recursive_loop(
+				start_cube_pos,
+				ROTATIONS[rotations_index],
+				&mut included_cube_pos,
+				best_encoding,
+				rotations_index,
+				polycube.n - 1,
+				0)
+			.map(|(least_sig_cube_pos, encoding, _offset)| (encoding, least_sig_cube_pos))
+	}
+}
+
+// uses make_encoding_iterative(), an iterative depth-first encoding
+//   of all cubes using a stack
+pub struct IterativeCanonicalizer;
+
+impl Canonicalizer for IterativeCanonicalizer {
+	fn name(&self) -> &'static str {
+		"iterative"
+	}
+
+	fn make_encoding(&self, polycube: &Polycube, start_cube_pos: isize, rotations_index: usize, best_encoding: u128) -> Option<(u128, isize)> {
+		polycube.make_encoding_iterative(
+				start_cube_pos,
+				best_encoding,
+				rotations_index)
+			.map(|(least_sig_cube_pos, encoding, _offset)| (encoding, least_sig_cube_pos))
+	}
+}
+
+// the most rotations a start cube can have, rounded up to a whole
+//   number of 16-byte vectors
+const LANES: usize = 32;
+
+// the vector instructions used by LanesCanonicalizer to rotate and
+//   compare the cube values of all its lanes at once
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LaneBackend {
+	Scalar,
+	Sse2,
+	Neon
+}
+
+static LANE_BACKEND: OnceLock<LaneBackend> = OnceLock::new();
+
+// the fastest backend this cpu supports, checked once at runtime
+pub fn lane_backend() -> LaneBackend {
+	*LANE_BACKEND.get_or_init(|| {
+		#[cfg(target_arch = "x86_64")]
+		{
+			if is_x86_feature_detected!("sse2") {
+				return LaneBackend::Sse2;
+			}
+		}
+		#[cfg(target_arch = "aarch64")]
+		{
+			if std::arch::is_aarch64_feature_detected!("neon") {
+				return LaneBackend::Neon;
+			}
+		}
+		LaneBackend::Scalar
+	})
+}
+
+// the rotation of each lane
+// a rotation moves each of the 6 neighbor bits of a cube value to
+//   another bit (ROTATION_TABLE[a | b] is ROTATION_TABLE[a] |
+//   ROTATION_TABLE[b]), so the vector backends rotate the cube values
+//   of all the lanes by looking at one neighbor bit at a time, rather
+//   than looking up each lane's value in ROTATION_TABLE
+pub struct LaneRotations {
+	count: usize,
+	indices: [u8; LANES],
+	// where each lane's rotation moves each neighbor bit to
+	bits: [[u8; LANES]; 6]
+}
+
+impl LaneRotations {
+	pub fn new(rotations_indices: &[u8]) -> LaneRotations {
+		let mut rotations = LaneRotations {
+			count: rotations_indices.len(),
+			indices: [0; LANES],
+			bits: [[0; LANES]; 6]
+		};
+		for (lane, rotations_index) in rotations_indices.iter().enumerate() {
+			rotations.indices[lane] = *rotations_index;
+			for bit in 0..6 {
+				rotations.bits[bit][lane] = ROTATION_TABLE[1 << bit][*rotations_index as usize];
+			}
+		}
+		rotations
+	}
+}
+
+// rotates the cube value of each lane by that lane's rotation, and
+//   returns the largest rotated value and a bitmask of the alive
+//   lanes with that value (dead lanes must have a cube value of 0)
+pub fn lanes_max_scalar(rotations: &LaneRotations, cube_encs: &[u8; LANES], alive: u32) -> (u8, u32) {
+	let mut values: [u8; LANES] = [0; LANES];
+	for lane in 0..rotations.count {
+		values[lane] = ROTATION_TABLE[cube_encs[lane] as usize][rotations.indices[lane] as usize];
+	}
+	let max = *values.iter().max().unwrap();
+	let mut at_max: u32 = 0;
+	for (lane, value) in values.iter().enumerate() {
+		if *value == max {
+			at_max |= 1 << lane;
+		}
+	}
+	(max, at_max & alive)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn lanes_max_sse2(rotations: &LaneRotations, cube_encs: &[u8; LANES], alive: u32) -> (u8, u32) {
+	use std::arch::x86_64::*;
+	let encs_low = _mm_loadu_si128(cube_encs.as_ptr() as *const __m128i);
+	let encs_high = _mm_loadu_si128(cube_encs.as_ptr().add(16) as *const __m128i);
+	let mut low = _mm_setzero_si128();
+	let mut high = _mm_setzero_si128();
+	for bit in 0..6 {
+		// keep where the bit moves to in the lanes that have the bit
+		let bit_splat = _mm_set1_epi8(1 << bit);
+		let bits = rotations.bits[bit].as_ptr();
+		let has_low = _mm_cmpeq_epi8(_mm_and_si128(encs_low, bit_splat), bit_splat);
+		let has_high = _mm_cmpeq_epi8(_mm_and_si128(encs_high, bit_splat), bit_splat);
+		low = _mm_or_si128(low, _mm_and_si128(has_low, _mm_loadu_si128(bits as *const __m128i)));
+		high = _mm_or_si128(high, _mm_and_si128(has_high, _mm_loadu_si128(bits.add(16) as *const __m128i)));
+	}
+	// fold the 32 values in half until the max is in the first byte
+	let mut max = _mm_max_epu8(low, high);
+	max = _mm_max_epu8(max, _mm_srli_si128(max, 8));
+	max = _mm_max_epu8(max, _mm_srli_si128(max, 4));
+	max = _mm_max_epu8(max, _mm_srli_si128(max, 2));
+	max = _mm_max_epu8(max, _mm_srli_si128(max, 1));
+	let max = (_mm_cvtsi128_si32(max) & 0xff) as u8;
+	let max_splat = _mm_set1_epi8(max as i8);
+	let low_mask = _mm_movemask_epi8(_mm_cmpeq_epi8(low, max_splat)) as u32;
+	let high_mask = _mm_movemask_epi8(_mm_cmpeq_epi8(high, max_splat)) as u32;
+	(max, (low_mask | (high_mask << 16)) & alive)
+}
+
+#[cfg(target_arch = "aarch64")]
+#[target_feature(enable = "neon")]
+unsafe fn lanes_max_neon(rotations: &LaneRotations, cube_encs: &[u8; LANES], alive: u32) -> (u8, u32) {
+	use std::arch::aarch64::*;
+	let encs_low = vld1q_u8(cube_encs.as_ptr());
+	let encs_high = vld1q_u8(cube_encs.as_ptr().add(16));
+	let mut low = vdupq_n_u8(0);
+	let mut high = vdupq_n_u8(0);
+	for bit in 0..6 {
+		// keep where the bit moves to in the lanes that have the bit
+		let bit_splat = vdupq_n_u8(1 << bit);
+		let bits = rotations.bits[bit].as_ptr();
+		low = vorrq_u8(low, vandq_u8(vtstq_u8(encs_low, bit_splat), vld1q_u8(bits)));
+		high = vorrq_u8(high, vandq_u8(vtstq_u8(encs_high, bit_splat), vld1q_u8(bits.add(16))));
+	}
+	let max = vmaxvq_u8(vmaxq_u8(low, high));
+	let max_splat = vdupq_n_u8(max);
+	// there's no movemask on neon, so keep one bit per lane and add
+	//   up each half of 8 lanes to make the bitmask
+	let lane_bits: [u8; 16] = [1, 2, 4, 8, 16, 32, 64, 128, 1, 2, 4, 8, 16, 32, 64, 128];
+	let lane_bits = vld1q_u8(lane_bits.as_ptr());
+	let low_bits = vandq_u8(vceqq_u8(low, max_splat), lane_bits);
+	let high_bits = vandq_u8(vceqq_u8(high, max_splat), lane_bits);
+	let mask = (vaddv_u8(vget_low_u8(low_bits)) as u32)
+		| ((vaddv_u8(vget_high_u8(low_bits)) as u32) << 8)
+		| ((vaddv_u8(vget_low_u8(high_bits)) as u32) << 16)
+		| ((vaddv_u8(vget_high_u8(high_bits)) as u32) << 24);
+	(max, mask & alive)
+}
+
+pub fn lanes_max(backend: LaneBackend, rotations: &LaneRotations, cube_encs: &[u8; LANES], alive: u32) -> (u8, u32) {
+	match backend {
+		// the backend is only chosen when the cpu supports it
+		#[cfg(target_arch = "x86_64")]
+		LaneBackend::Sse2 => unsafe { lanes_max_sse2(rotations, cube_encs, alive) },
+		#[cfg(target_arch = "aarch64")]
+		LaneBackend::Neon => unsafe { lanes_max_neon(rotations, cube_encs, alive) },
+		_ => lanes_max_scalar(rotations, cube_encs, alive)
+	}
+}
+
+// the polycube's cubes numbered 0..n (in position order) with their
+//   neighbors as cube numbers, so each lane can keep track of the
+//   cubes it has encoded in a bitmask
+// these are kept in arrays rather than vecs so that nothing is
+//   allocated for each encoding
+pub struct NumberedCubes {
+	n: usize,
+	positions: [isize; MAX_N],
+	encs: [u8; MAX_N],
+	// NO_CUBE where there is no neighbor
+	neighbors: [[u8; 6]; MAX_N]
+}
+
+const NO_CUBE: u8 = u8::MAX;
+
+impl NumberedCubes {
+	pub fn new(polycube: &Polycube) -> NumberedCubes {
+		let mut cubes = NumberedCubes {
+			n: polycube.cube_info_by_pos.len(),
+			positions: [0; MAX_N],
+			encs: [0; MAX_N],
+			neighbors: [[NO_CUBE; 6]; MAX_N]
+		};
+		for (cube, pos) in polycube.cube_info_by_pos.keys().enumerate() {
+			cubes.positions[cube] = *pos;
+		}
+		for (cube, cube_info) in polycube.cube_info_by_pos.values().enumerate() {
+			cubes.encs[cube] = cube_info[6].unwrap() as u8;
+			for direction in DIRECTIONS {
+				if let Some(neighbor_pos) = cube_info[direction] {
+					cubes.neighbors[cube][direction] = cubes.number(neighbor_pos);
+				}
+			}
+		}
+		cubes
+	}
+
+	pub fn number(&self, pos: isize) -> u8 {
+		self.positions[..self.n].binary_search(&pos).unwrap() as u8
+	}
+}
+
+// each lane's stack holds the cubes still to be visited, where each
+//   cube pushes at most 5 neighbors (one neighbor led to it)
+const LANE_STACK_SIZE: usize = 5 * MAX_N + 1;
+// the stacks are shared by all of a polycube's start cubes, since
+//   clearing them for each one takes longer than some encodings
+type LaneStacks = [[u8; LANE_STACK_SIZE]; LANES];
+
+// walks the depth-first encodings for all of a start cube's rotations
+//   in lockstep, one cube per step, so the cube values of all the
+//   lanes (one lane per rotation) can be rotated and compared at once
+// since all lanes still alive share the same encoding so far, a lane
+//   is dropped as soon as its cube value is less than another lane's,
+//   and all lanes are dropped as soon as the encoding falls below
+//   best_encoding
+// with the scalar_only flag set, vector instructions aren't used (so
+//   the vector backends can be checked against it)
+pub struct LanesCanonicalizer {
+	pub scalar_only: bool
+}
+
+impl LanesCanonicalizer {
+	fn backend(&self) -> LaneBackend {
+		if self.scalar_only {
+			LaneBackend::Scalar
+		} else {
+			lane_backend()
+		}
+	}
+
+	// returns the encoding shared by the lanes that survive to the
+	//   end, a bitmask of those lanes, and the least significant cube
+	//   number for each lane, or None if the encoding fell below
+	//   best_encoding
+	fn make_lanes_encoding(&self, cubes: &NumberedCubes, stacks: &mut LaneStacks, start_cube: u8, rotations_indices: &[u8], best_encoding: u128) -> Option<(u128, u32, [u8; LANES])> {
+		let backend = self.backend();
+		let n = cubes.n;
+		let lane_count = rotations_indices.len();
+		let rotations = LaneRotations::new(rotations_indices);
+		let mut stack_lens: [usize; LANES] = [0; LANES];
+		let mut included: [u32; LANES] = [0; LANES];
+		let mut least_sig_cubes: [u8; LANES] = [NO_CUBE; LANES];
+		// the unrotated value of the cube each lane encodes at each step
+		let mut cube_encs: [u8; LANES] = [0; LANES];
+		let mut alive: u32 = if lane_count == 32 { u32::MAX } else { (1 << lane_count) - 1 };
+		for lane in 0..lane_count {
+			stacks[lane][0] = start_cube;
+			stack_lens[lane] = 1;
+		}
+		let mut encoding: u128 = 0;
+		// once the encoding so far is larger than best_encoding's, the
+		//   rest of best_encoding no longer matters
+		let mut above_best = false;
+		for step in 0..n {
+			for lane in 0..lane_count {
+				if alive & (1 << lane) == 0 {
+					cube_encs[lane] = 0;
+					continue;
+				}
+				let rotation = ROTATIONS[rotations_indices[lane] as usize];
+				let stack = &mut stacks[lane];
+				let mut cube: u8;
+				loop {
+					stack_lens[lane] -= 1;
+					cube = stack[stack_lens[lane]];
+					if included[lane] & (1 << cube) == 0 {
+						break;
+					}
+				}
+				included[lane] |= 1 << cube;
+				least_sig_cubes[lane] = cube;
+				cube_encs[lane] = cubes.encs[cube as usize];
+				// push in reverse so that the neighbors are popped off
+				//   the stack in the rotation's order
+				for direction in rotation.iter().rev() {
+					let neighbor = cubes.neighbors[cube as usize][*direction];
+					if neighbor != NO_CUBE && included[lane] & (1 << neighbor) == 0 {
+						stack[stack_lens[lane]] = neighbor;
+						stack_lens[lane] += 1;
+					}
+				}
+			}
+			let (max, at_max) = lanes_max(backend, &rotations, &cube_encs, alive);
+			alive = at_max;
+			encoding = (encoding << 6) + max as u128;
+			if !above_best {
+				let best_value = ((best_encoding >> ((n - 1 - step) * 6)) & 63) as u8;
+				if max < best_value {
+					return None;
+				}
+				above_best = max > best_value;
+			}
+		}
+		Some((encoding, alive, least_sig_cubes))
+	}
+}
+
+// a start cube's rotations to encode together, leaving out the best
+//   pair's rotation if it's this start cube's, since the best pair is
+//   encoded first on its own
+fn rotations_without_best<'a>(rotations_indices: &'a [u8], cube_pos: isize, best: Option<(isize, u8)>, other_rotations: &'a mut [u8; 24]) -> &'a [u8] {
+	match best {
+		Some((best_pos, best_rotations_index)) if best_pos == cube_pos => {
+			let mut count = 0;
+			for rotations_index in rotations_indices.iter().filter(|rotations_index| **rotations_index != best_rotations_index) {
+				other_rotations[count] = *rotations_index;
+				count += 1;
+			}
+			&other_rotations[..count]
+		}
+		_ => rotations_indices
+	}
+}
+
+impl Canonicalizer for LanesCanonicalizer {
+	fn name(&self) -> &'static str {
+		if self.scalar_only {
+			"lanes-scalar"
+		} else {
+			"lanes"
+		}
+	}
+
+	fn make_encoding(&self, polycube: &Polycube, start_cube_pos: isize, rotations_index: usize, best_encoding: u128) -> Option<(u128, isize)> {
+		// a single lane
+		let cubes = NumberedCubes::new(polycube);
+		let start_cube = cubes.number(start_cube_pos);
+		self.make_lanes_encoding(&cubes, &mut [[0; LANE_STACK_SIZE]; LANES], start_cube, &[rotations_index as u8], best_encoding)
+			.map(|(encoding, _alive, least_sig_cubes)| (encoding, cubes.positions[least_sig_cubes[0] as usize]))
+	}
+
+	// the same as the default find_canonical_info(), but with all of a
+	//   start cube's rotations encoded together
+	fn find_canonical_info(&self, polycube: &Polycube, look_for_pos_as_least_significant: Option<isize>, target_encoding: u128) -> CanonicalInfo {
+		let mut canonical = CanonicalInfo {
+			enc: 0,
+			least_significant_cube_pos: None,
+			max_cube_value: polycube.find_maximum_cube_value(),
+			symmetry_order: 0,
+			start: None
+		};
+		let cubes = NumberedCubes::new(polycube);
+		let mut stacks: LaneStacks = [[0; LANE_STACK_SIZE]; LANES];
+		let mut best_encoding: u128 = target_encoding;
+		let mut encode = |cube_pos: isize, rotations_indices: &[u8]| {
+			let start_cube = cubes.number(cube_pos);
+			if let Some((encoding, alive, least_sig_cubes)) = self.make_lanes_encoding(&cubes, &mut stacks, start_cube, rotations_indices, best_encoding) {
+				// go through the surviving lanes in rotation order, as
+				//   the default find_canonical_info() would
+				for lane in (0..rotations_indices.len()).filter(|lane| alive & (1 << lane) != 0) {
+					let least_significant_cube_pos = cubes.positions[least_sig_cubes[lane] as usize];
+					if encoding > best_encoding {
+						canonical.enc = encoding;
+						canonical.least_significant_cube_pos = Some(least_significant_cube_pos);
+						canonical.symmetry_order = 1;
+						canonical.start = Some((cube_pos, rotations_indices[lane]));
+						best_encoding = encoding;
+					} else if encoding == best_encoding {
+						canonical.symmetry_order += 1;
+						if Some(least_significant_cube_pos) == look_for_pos_as_least_significant {
+							canonical.least_significant_cube_pos = Some(least_significant_cube_pos);
+						}
+					}
+				}
+			}
+		};
+		// the best pair kept from the last canonical encoding is encoded
+		//   first on its own, as in Polycube::start_pairs()
+		let best = polycube.start_cubes.best;
+		if let Some((cube_pos, rotations_index)) = best {
+			encode(cube_pos, &[rotations_index]);
+		}
+		let mut other_rotations = [0; 24];
+		for cube_pos in polycube.start_cube_positions().iter() {
+			let rotations_indices = MAXIMUM_CUBE_ROTATION_INDICES[polycube.cube_info_by_pos[cube_pos][6].unwrap() as usize];
+			let rotations_indices = rotations_without_best(rotations_indices, *cube_pos, best, &mut other_rotations);
+			if !rotations_indices.is_empty() {
+				encode(*cube_pos, rotations_indices);
+			}
+		}
+		canonical
+	}
+
+	fn find_canonical_enc(&self, polycube: &Polycube, target_encoding: u128) -> u128 {
+		let cubes = NumberedCubes::new(polycube);
+		let mut stacks: LaneStacks = [[0; LANE_STACK_SIZE]; LANES];
+		let mut canonical_enc: u128 = 0;
+		let mut best_encoding: u128 = target_encoding;
+		let mut encode = |cube_pos: isize, rotations_indices: &[u8]| {
+			let start_cube = cubes.number(cube_pos);
+			if let Some((encoding, _alive, _least_sig_cubes)) = self.make_lanes_encoding(&cubes, &mut stacks, start_cube, rotations_indices, best_encoding) {
+				if encoding >= best_encoding {
+					canonical_enc = encoding;
+					best_encoding = encoding;
+				}
+			}
+		};
+		let best = polycube.start_cubes.best;
+		if let Some((cube_pos, rotations_index)) = best {
+			encode(cube_pos, &[rotations_index]);
+		}
+		let mut other_rotations = [0; 24];
+		for cube_pos in polycube.start_cube_positions().iter() {
+			let rotations_indices = MAXIMUM_CUBE_ROTATION_INDICES[polycube.cube_info_by_pos[cube_pos][6].unwrap() as usize];
+			let rotations_indices = rotations_without_best(rotations_indices, *cube_pos, best, &mut other_rotations);
+			if !rotations_indices.is_empty() {
+				encode(*cube_pos, rotations_indices);
+			}
+		}
+		canonical_enc
+	}
+}
+// all available canonicalizers, where the first is the default
+pub const CANONICALIZERS: [&dyn Canonicalizer; 5] = [
+	&RecursiveCanonicalizer,
+	&RecursiveLoopCanonicalizer,
+	&IterativeCanonicalizer,
+	&LanesCanonicalizer { scalar_only: false },
+	&LanesCanonicalizer { scalar_only: true }];
+
+static SELECTED_CANONICALIZER: AtomicUsize = AtomicUsize::new(0);
+
+// whether the start cube and rotation that gave a polycube's last
+//   canonical encoding are kept to be tried first, which only changes
+//   how fast encodings are found (turned off to check that)
+static REUSE_BEST_START: AtomicBool = AtomicBool::new(true);
+
+pub fn canonicalizer() -> &'static dyn Canonicalizer {
+	CANONICALIZERS[SELECTED_CANONICALIZER.load(Ordering::Relaxed)]
+}
+
+// select the canonicalizer used by all threads, by name
+pub fn select_canonicalizer(name: &str) -> Result<(), String> {
+	match CANONICALIZERS.iter().position(|c| c.name() == name) {
+		Some(index) => {
+			SELECTED_CANONICALIZER.store(index, Ordering::Relaxed);
+			Ok(())
+		}
+		None => Err(format!("unknown canonicalizer [{}], expected one of: {}",
+			name, CANONICALIZERS.iter().map(|c| c.name()).collect::<Vec<&str>>().join(", ")))
+	}
+}
+
+// grow a polycube of size n by adding cubes at random free positions
+//   next to random cubes (this is not a uniform sample of polycubes,
+//   but it's good enough for comparing encodings)
+pub fn random_polycube(n: u8, rng: &mut impl Rng) -> Polycube {
+	let mut polycube = Polycube::new(true);
+	while polycube.n < n {
+		let cube_positions: Vec<isize> = polycube.cube_info_by_pos.keys().cloned().collect();
+		let cube_pos = cube_positions[rng.gen_range(0..cube_positions.len())];
+		let try_pos = cube_pos + DIRECTION_COSTS[rng.gen_range(0..6)];
+		if !polycube.cube_info_by_pos.contains_key(&try_pos) {
+			polycube.add(try_pos);
+		}
+	}
+	polycube
+}
+
+// remove and add back random cubes, checking after each step that
+//   the start cubes kept up to date by add() and remove() are the
+//   same as those found by looking at every cube
+pub fn check_start_cubes(polycube: &mut Polycube, rng: &mut impl Rng) -> bool {
+	let mut removed: Vec<isize> = Vec::new();
+	// the best start cube and rotation of the canonical encoding must
+	//   also be dropped once they aren't a start cube and one of its
+	//   best rotations
+	let check = |polycube: &mut Polycube| -> bool {
+		polycube.refresh_start_cubes();
+		polycube.start_cubes.positions == polycube.find_start_cubes()
+			&& polycube.start_cubes.best.is_none_or(|(best_pos, rotations_index)|
+				polycube.start_cubes.positions.contains(&best_pos)
+					&& MAXIMUM_CUBE_ROTATION_INDICES[polycube.cube_info_by_pos[&best_pos][6].unwrap() as usize].contains(&rotations_index))
+	};
+	polycube.find_canonical_info(None);
+	while polycube.n > 1 {
+		let cube_positions: Vec<isize> = polycube.cube_info_by_pos.keys().cloned().collect();
+		let cube_pos = cube_positions[rng.gen_range(0..cube_positions.len())];
+		polycube.remove(cube_pos);
+		removed.push(cube_pos);
+		if !check(polycube) {
+			return false;
+		}
+	}
+	while let Some(cube_pos) = removed.pop() {
+		polycube.add(cube_pos);
+		if !check(polycube) {
+			return false;
+		}
+	}
+	true
+}
+
+// check that every canonicalizer finds the same canonical encoding
+//   and least significant cube for the same random polycubes, and
+//   that each encoding rebuilds a polycube with that same encoding
+// this also checks the start cubes kept by add() and remove()
+// returns the number of mismatches found
+pub fn check_canonicalizers(polycube_count: usize) -> usize {
+	let mut rng = thread_rng();
+	let mut mismatches: usize = 0;
+	for i in 0..polycube_count {
+		// 21 cubes fit in the 128-bit encoding
+		let polycube = random_polycube(rng.gen_range(2..22), &mut rng);
+		let reference = CANONICALIZERS[0].find_canonical_info(&polycube, None, 0);
+		let mut rebuilt = Polycube::new(false);
+		rebuilt.rebuild_from_encoding(reference.enc);
+		let rebuilt_enc = CANONICALIZERS[0].find_canonical_info(&rebuilt, None, 0).enc;
+		if rebuilt_enc != reference.enc {
+			mismatches += 1;
+			println!("polycube {} (n={}): encoding [{}] rebuilds a polycube with encoding [{}]",
+				i, polycube.n, encoding_to_str(reference.enc), encoding_to_str(rebuilt_enc));
+		}
+		// the same polycube moved far from the origin must encode the same
+		let max_offset = MAX_COORDINATE - polycube.n as isize;
+		let offset = (rng.gen_range(-max_offset..=max_offset), rng.gen_range(-max_offset..=max_offset), rng.gen_range(-max_offset..=max_offset));
+		let moved_coordinates: Vec<(isize, isize, isize)> = polycube.coordinates().iter()
+			.map(|(x, y, z)| (x + offset.0, y + offset.1, z + offset.2))
+			.collect();
+		let moved_enc = match Polycube::from_coordinates(&moved_coordinates) {
+			Ok(moved) => CANONICALIZERS[0].find_canonical_info(&moved, None, 0).enc,
+			Err(err) => {
+				println!("polycube {} (n={}): could not be moved by {:?}: {}", i, polycube.n, offset, err);
+				0
+			}
+		};
+		if moved_enc != reference.enc {
+			mismatches += 1;
+			println!("polycube {} (n={}): encoding [{}] became [{}] when moved by {:?}",
+				i, polycube.n, encoding_to_str(reference.enc), encoding_to_str(moved_enc), offset);
+		}
+		if !check_start_cubes(&mut polycube.copy(), &mut rng) {
+			mismatches += 1;
+			println!("polycube {} (n={}) [{}]: start cubes kept by add() and remove() don't match those found from scratch",
+				i, polycube.n, encoding_to_str(reference.enc));
+		}
+		for c in CANONICALIZERS.iter().skip(1) {
+			let canonical = c.find_canonical_info(&polycube, None, 0);
+			let enc_with_target = c.find_canonical_enc(&polycube, reference.enc);
+			if canonical.enc != reference.enc
+					|| canonical.least_significant_cube_pos != reference.least_significant_cube_pos
+					|| enc_with_target != reference.enc {
+				mismatches += 1;
+				println!("polycube {} (n={}): [{}] found encoding [{}] (with target: [{}]) and least significant cube {:?}, but [{}] found [{}] and {:?}",
+					i, polycube.n,
+					c.name(), encoding_to_str(canonical.enc), encoding_to_str(enc_with_target), canonical.least_significant_cube_pos,
+					CANONICALIZERS[0].name(), encoding_to_str(reference.enc), reference.least_significant_cube_pos);
+			}
+		}
+	}
+	mismatches
+}
+
+// time each canonicalizer finding the canonical encodings of the same
+//   random polycubes, to compare their speeds without the rest of a
+//   count (where the encoding is only part of the time)
+// returns the seconds each canonicalizer took, in CANONICALIZERS order
+pub fn time_canonicalizers(polycube_count: usize) -> Vec<f64> {
+	let mut rng = thread_rng();
+	let polycubes: Vec<Polycube> = (0..polycube_count).map(|_| random_polycube(rng.gen_range(2..22), &mut rng)).collect();
+	CANONICALIZERS.iter().map(|c| {
+		let start_time = Instant::now();
+		for polycube in polycubes.iter() {
+			std::hint::black_box(c.find_canonical_info(polycube, None, 0));
+		}
+		start_time.elapsed().as_secs_f64()
+	}).collect()
+}
+
+// count the polycubes of each size up to n with each canonicalizer,
+//   single-threaded, and compare the counts with the well-known ones,
+//   which checks the canonicalizers and the start cubes kept by add()
+//   and remove() over every polycube rather than random ones
+// returns the number of wrong counts
+pub fn check_canonicalizer_counts(n: u8) -> usize {
+	let monocube_enc = Polycube::new(true).find_canonical_info(None).enc;
+	let selected = SELECTED_CANONICALIZER.load(Ordering::Relaxed);
+	let mut mismatches: usize = 0;
+	for (index, c) in CANONICALIZERS.iter().enumerate() {
+		SELECTED_CANONICALIZER.store(index, Ordering::Relaxed);
+		let start_time = Instant::now();
+		let counts = subtree_counts(monocube_enc, n, 0, 0);
+		println!("  {:<16} counted up to n={} in {:.3} seconds", c.name(), n, start_time.elapsed().as_secs_f64());
+		for i in 1..n as usize + 1 {
+			if counts[i] != WELL_KNOWN_N_COUNTS[i] {
+				mismatches += 1;
+				println!("  [{}] counted {} polycubes for n={}, but there are {}", c.name(), counts[i], i, WELL_KNOWN_N_COUNTS[i]);
+			}
+		}
+	}
+	SELECTED_CANONICALIZER.store(selected, Ordering::Relaxed);
+	mismatches
+}
+
+// count the polycubes in the subtrees of the given polycubes up to n,
+//   both with and without the best start cube and rotation of each
+//   polycube's last canonical encoding being tried first, which must
+//   give the same counts
+// returns the number of subtrees whose counts differ
+pub fn check_reused_start_counts(polycube_encs: &[u128], n: u8) -> usize {
+	let reuse = REUSE_BEST_START.load(Ordering::Relaxed);
+	let mut mismatches: usize = 0;
+	for polycube_enc in polycube_encs.iter() {
+		REUSE_BEST_START.store(false, Ordering::Relaxed);
+		let counts_without = subtree_counts(*polycube_enc, n, 0, 0);
+		REUSE_BEST_START.store(true, Ordering::Relaxed);
+		let counts_with = subtree_counts(*polycube_enc, n, 0, 0);
+		if counts_with != counts_without {
+			mismatches += 1;
+			println!("  [{}] counted {:?} when reusing the best start cube, but {:?} without",
+				encoding_to_str(*polycube_enc), &counts_with[..n as usize + 1], &counts_without[..n as usize + 1]);
+		}
+	}
+	REUSE_BEST_START.store(reuse, Ordering::Relaxed);
+	mismatches
+}
+
+static mut N_COUNTS: [usize; 23] = [0; 23];
+
+// panics raised with panic!() or unwrap() carry a &str or String
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+	if let Some(message) = payload.downcast_ref::<&str>() {
+		message.to_string()
+	} else if let Some(message) = payload.downcast_ref::<String>() {
+		message.clone()
+	} else {
+		"(no message)".to_string()
+	}
+}
+
+// push onto a bounded queue, waiting while the queue is full
+// if a halt flag is given and a halt is signalled while waiting,
+//   give up and hand the item back to the caller
+pub fn push_with_backpressure<T>(queue: &ArrayQueue<T>, mut item: T, atomic_halt: Option<&AtomicBool>) -> Result<(), T> {
+	loop {
+		match queue.push(item) {
+			Ok(_) => {
+				return Ok(());
+			}
+			Err(rejected) => {
+				item = rejected;
+			}
+		}
+		if let Some(halt) = atomic_halt {
+			if halt.load(Ordering::Relaxed) {
+				return Err(item);
+			}
+		}
+		thread::sleep(QUEUE_FULL_WAIT);
+	}
+}
+
+// responses are always pushed, even after a halt, because the
+//   main thread keeps draining the response queue until every
+//   worker has finished
+pub fn push_response(response_queue: &ArrayQueue<ThreadResponse>, response: ThreadResponse) {
+	if push_with_backpressure(response_queue, response, None).is_err() {
+		panic!("response_queue.push() failed");
+	}
+}
+
+//  the initial delegator worker begins here
+// it starts from the single cube, or when resuming, from the frontier
+//   left by a halted delegator, whose polycubes were already counted
+// returns the frontier it leaves if halted: the counted polycubes
+//   smaller than spawn_n whose subtrees it hasn't enumerated
+pub fn extend_and_delegate_outer(start_encs: Vec<u128>, n: u8, atomic_halt: Arc<AtomicBool>,
+		submit_queue: Arc<ArrayQueue<u128>>, response_queue: Arc<ArrayQueue<ThreadResponse>>, spawn_n: u8) -> Vec<u128> {
+	let mut all_n_counts: [usize; MAX_N] = [0; MAX_N];
+	let mut frontier: Vec<u128> = Vec::new();
+	for start_enc in start_encs {
+		if atomic_halt.load(Ordering::Relaxed) {
+			frontier.push(start_enc);
+			continue;
+		}
+		let mut polycube = Polycube::new(false);
+		polycube.rebuild_from_encoding(start_enc);
+		let found_counts_by_n = extend_and_delegate(
+				&polycube,
+				n,
+				spawn_n,
+				&submit_queue,
+				&response_queue,
+				&atomic_halt,
+				&mut frontier);
+		for i in 1..n+1 {
+			all_n_counts[i as usize] += found_counts_by_n[i as usize];
+		}
+	}
+	flush_search_stats();
+	push_response(&response_queue, ThreadResponse{
+		job_complete: true,
+		results: Some(all_n_counts),
+		orig_polycube_enc: None,
+		final_polycube_encs: Vec::new(),
+		failure: None,
+		job_sec: None,
+		job_features: None });
+	frontier
+}
+
+// when resuming or beginning from a file, this thread stands in
+//   for the initial delegator: it streams the jobs from the file
+//   into the bounded submit queue as the workers make room for them
+// the reader is handed back when done, so that if we were halted,
+//   the jobs that were never submitted can be copied from it into
+//   the new resume file
+pub fn feed_jobs_outer(mut jobs: JobFileReader, atomic_halt: Arc<AtomicBool>, submit_queue: Arc<ArrayQueue<u128>>) -> JobFileReader {
+	while let Some(enc) = jobs.next() {
+		if let Err(enc) = push_with_backpressure(&submit_queue, enc, Some(&atomic_halt)) {
+			jobs.push_back(enc);
+			break;
+		}
+	}
+	jobs
+}
+
+// for tests, the number of attempts at each job that should panic
+#[cfg(test)]
+thread_local! {
+	static INJECTED_JOB_PANICS: RefCell<BTreeMap<u128, usize>> = const { RefCell::new(BTreeMap::new()) };
+}
+
+pub fn extend_as_worker_outer(
+		n: u8,
+		atomic_halt: Arc<AtomicBool>,
+		atomic_done: Arc<AtomicBool>,
+		atomic_waiting: Arc<AtomicBool>,
+		submit_queue: Arc<ArrayQueue<u128>>,
+		response_queue: Arc<ArrayQueue<ThreadResponse>>,
+		save_final_polycubes: bool) {
+	let mut halted = false;
+	// thread-local random generator
+	let mut rng = thread_rng();
+	// by default, we are not signalling that this worker
 	//   is waiting, so start with true here
 	let mut signalled_waiting_false = true;
 	while !halted {
-		let polycube = submit_queue.pop();
-		if polycube.is_none() {
+		let polycube_enc = submit_queue.pop();
+		if polycube_enc.is_none() {
 			atomic_waiting.store(true, Ordering::Release);
 			signalled_waiting_false = false;
 			thread::sleep(Duration::from_millis(rng.gen_range(25..75)));
@@ -1092,19 +2613,69 @@ pub fn extend_as_worker_outer(
 			atomic_waiting.store(false, Ordering::Release);
 			signalled_waiting_false = true;
 		}
-		let mut polycube = polycube.unwrap();
-		// save a copy of the original polycube so we can
-		//   write it to disk if we are halted
-		let polycube_orig_clone = polycube.copy();
-		let mut final_polycube_encs: Vec<u128> = Vec::new();
-		match extend_as_worker(
-				&mut polycube,
-				n,
-				&submit_queue,
-				&atomic_halt,
-				&mut rng,
-				save_final_polycubes,
-				&mut final_polycube_encs) {
+		// jobs arrive as canonical encodings, so rebuild the polycube
+		//   (we keep the encoding so we can write it to disk if we are halted)
+		let polycube_enc = polycube_enc.unwrap();
+		// a panic while evaluating a job must neither take down the
+		//   worker nor leave a partial count behind, so each attempt is
+		//   caught and its counts thrown away, and the job is retried
+		//   from a freshly rebuilt polycube
+		// a job that panics on every attempt is sent back to be
+		//   quarantined instead of counted
+		let mut evaluated: Option<(Option<[usize; 23]>, Vec<u128>, f64)> = None;
+		let mut failure: Option<String> = None;
+		for attempt in 1..=JOB_ATTEMPTS {
+			let mut final_polycube_encs: Vec<u128> = Vec::new();
+			let job_start = Instant::now();
+			let result = catch_unwind(AssertUnwindSafe(|| {
+				#[cfg(test)]
+				INJECTED_JOB_PANICS.with(|panics| {
+					if let Some(remaining) = panics.borrow_mut().get_mut(&polycube_enc).filter(|remaining| **remaining > 0) {
+						*remaining -= 1;
+						panic!("injected panic");
+					}
+				});
+				let mut polycube = Polycube::new(false);
+				polycube.rebuild_from_encoding(polycube_enc);
+				let found_counts_by_n = extend_as_worker(
+					&mut polycube,
+					n,
+					&submit_queue,
+					&atomic_halt,
+					&mut rng,
+					save_final_polycubes,
+					&mut final_polycube_encs);
+				polycube.record_search_stats();
+				found_counts_by_n
+			}));
+			match result {
+				Ok(found_counts_by_n) => {
+					evaluated = Some((found_counts_by_n, final_polycube_encs, job_start.elapsed().as_secs_f64()));
+					break;
+				}
+				Err(payload) => {
+					let message = panic_message(payload.as_ref());
+					report_warning(format!("error: job [{}] panicked on attempt {} of {}: {}",
+						encoding_to_str(polycube_enc), attempt, JOB_ATTEMPTS, message));
+					failure = Some(message);
+				}
+			}
+		}
+		let (found_counts_by_n, final_polycube_encs, job_sec) = match evaluated {
+			Some(evaluated) => evaluated,
+			None => {
+				push_response(&response_queue, ThreadResponse{
+					job_complete: false,
+					results: None,
+					orig_polycube_enc: Some(polycube_enc),
+					final_polycube_encs: Vec::new(),
+					failure,
+					job_sec: None,
+					job_features: None });
+				continue;
+			}
+		};
+		match found_counts_by_n {
 			Some(found_counts_by_n) => {
 				let mut all_n_counts: [usize; MAX_N] = [0; MAX_N];
 				for i in 1..n+1 {
@@ -1115,1205 +2686,5008 @@ pub fn extend_as_worker_outer(
 						None => {}
 					}
 				}
-				match response_queue.push(ThreadResponse{
-						job_complete: true,
-						results: Some(all_n_counts),
-						orig_polycube: None,
-						final_polycube_encs: final_polycube_encs }) {
-					Ok(_) => {}
-					Err(_) => {
-						panic!("response_queue.push() failed");
-					}
-				}
+				push_response(&response_queue, ThreadResponse{
+					job_complete: true,
+					results: Some(all_n_counts),
+					orig_polycube_enc: None,
+					final_polycube_encs,
+					failure: None,
+					job_sec: Some(job_sec),
+					job_features: Some(JobFeatures::of_encoding(polycube_enc)) });
 			}
 			None => {
 				// stopped due to the halt
 				halted = true;
-				match response_queue.push(ThreadResponse{
-						job_complete: false,
-						results: None,
-						orig_polycube: Some(polycube_orig_clone),
-						final_polycube_encs: final_polycube_encs }) {
-					Ok(_) => {}
-					Err(_) => {
-						panic!("response_queue.push() failed");
-					}
+				push_response(&response_queue, ThreadResponse{
+					job_complete: false,
+					results: None,
+					orig_polycube_enc: Some(polycube_enc),
+					final_polycube_encs,
+					failure: None,
+					job_sec: None,
+					job_features: None });
+			}
+
+		}
+	}
+	// after halt, drain the queue
+	while let Some(polycube_enc) = submit_queue.pop() {
+		// put a message here to indicate that this Polycube is
+		//   unevaluated
+		push_response(&response_queue, ThreadResponse{
+			job_complete: false,
+			results: None,
+			orig_polycube_enc: Some(polycube_enc),
+			final_polycube_encs: Vec::new(),
+			failure: None,
+			job_sec: None,
+			job_features: None });
+	}
+	flush_search_stats();
+
+	//except HaltSignal:
+	//	# maybe indicate that the initial delegator worker was
+	//	#   halted with a special-case None value here
+	//	response_queue.put((False, None))
+}
+
+// expand the polycube until we reach n=delegate_at_n (spawn_n) and
+//   and that point, place the canonical encoding of any found
+//   polycubes to enumerate into the submit queue
+pub fn extend_and_delegate(polycube: &Polycube, limit_n: u8, delegate_at_n: u8,
+	submit_queue: &Arc<ArrayQueue<u128>>, response_queue: &Arc<ArrayQueue<ThreadResponse>>,
+	atomic_halt: &Arc<AtomicBool>, frontier: &mut Vec<u128>) -> [usize; 23] {
+
+	let mut found_counts_by_n: [usize; 23] = [0; 23];
+
+	// we are done if we've reached the desired n,
+	//   which we need to stop at because we are doing
+	//   a depth-first recursive evaluation
+	if polycube.n == limit_n {
+		return found_counts_by_n;
+	}
+
+	// keep a Set of all evaluated positions so we don't repeat them
+	let mut tried_pos: BTreeSet<isize> = BTreeSet::new();
+	tried_pos.extend(polycube.cube_info_by_pos.keys());
+
+	let mut tried_canonicals: BTreeSet<u128> = BTreeSet::new();
+
+	let mut canonical_try: &CanonicalInfo;
+	let mut canonical_try_clone: CanonicalInfo;
+	let mut least_significant_cube_pos: isize;
+
+	let mut tmp_add = polycube.copy();
+	let canonical_orig_enc: u128 = tmp_add.find_canonical_info(None).enc;
+	let symmetry_moves = tmp_add.find_symmetry_moves();
+	let parent_start_cubes = tmp_add.save_start_cubes();
+	let canonical_orig_enc_shifted = canonical_orig_enc << 6;
+
+	let mut try_pos: isize;
+
+	// once halted, the delegator stops descending: it still finds the
+	//   rest of this polycube's children, which is quick, but sends
+	//   back those of size spawn_n as unsubmitted jobs and adds the
+	//   smaller ones to the frontier, so that every job ends up either
+	//   evaluated, in the resume file, or under its frontier
+
+	// for each cube, for each direction, add a cube
+	for cube_pos in polycube.cube_info_by_pos.keys() {
+		for direction_cost in DIRECTION_COSTS {
+			try_pos = cube_pos + direction_cost;
+
+			// skip if we've already tried this position
+			if !tried_pos.insert(try_pos) {
+				continue;
+			}
+			tmp_add.skip_symmetric_positions(&symmetry_moves, try_pos, &mut tried_pos);
+
+			instrument!(c => c.candidates_tried += 1);
+			// create P+A
+			tmp_add.add(try_pos);
+
+			// skip if we've already seen some P+A with the same canonical representation
+			//   (comparing the bitwise int only)
+			canonical_try = tmp_add.find_canonical_info_with_target(Some(try_pos), canonical_orig_enc_shifted);
+			if !tried_canonicals.insert(canonical_try.enc) {
+				instrument!(c => c.duplicate_canonicals += 1);
+				tmp_add.remove(try_pos);
+				tmp_add.restore_start_cubes(&parent_start_cubes);
+				continue;
+			}
+
+			// (P+A always encodes to more than P's encoding shifted over
+			//   one cube, since a neighbor of A gains a bit before A itself
+			//   is reached, so this is always found)
+			least_significant_cube_pos = canonical_try.least_significant_cube_pos.unwrap();
+
+			// if try_pos (cube A) is the least significant, then P+A-A==P and P+A is a new unique polycube
+			if least_significant_cube_pos == try_pos {
+				instrument!(c => c.least_significant_fast_path += 1);
+				found_counts_by_n[tmp_add.n as usize] += 1;
+				// the initial delegator submits jobs for threads,
+				//   but only if the found polycube has n=spawn_n
+				if tmp_add.n == delegate_at_n {
+					// if the workers have fallen behind, wait here until
+					//   there is room in the queue
+					// once halted, the remaining jobs are sent back unevaluated
+					//   so they can be written to the resume file
+					let job_enc = tmp_add.find_canonical_info(None).enc;
+					if let Err(job_enc) = push_with_backpressure(submit_queue, job_enc, Some(atomic_halt)) {
+						push_response(response_queue, ThreadResponse{
+							job_complete: false,
+							results: None,
+							orig_polycube_enc: Some(job_enc),
+							final_polycube_encs: Vec::new(),
+							failure: None,
+							job_sec: None,
+							job_features: None });
+					}
+				} else if atomic_halt.load(Ordering::Relaxed) {
+					frontier.push(tmp_add.find_canonical_info(None).enc);
+				} else {
+					let futher_counts = extend_and_delegate(&tmp_add.copy(),
+							limit_n, delegate_at_n,
+							submit_queue, response_queue, atomic_halt, frontier);
+					for i in 1..limit_n+1 {
+						found_counts_by_n[i as usize] += futher_counts[i as usize];
+					}
+				}
+			} else {
+				canonical_try_clone = canonical_try.clone();
+				// remove the last of the ordered cubes (cube B) in P+A
+				tmp_add.remove(least_significant_cube_pos);
+				// if P+A-B has the same canonical representation as P, count P+A as a new unique polycube
+				//   and continue recursion into that P+A
+				// (the cube value counts are compared first since they rule
+				//   out many P+A-B without encoding them)
+				if tmp_add.matches_invariants(&polycube.cube_value_counts)
+						&& tmp_add.find_canonical_enc_with_target(canonical_orig_enc) == canonical_orig_enc {
+					instrument!(c => c.pab_checks_passed += 1);
+					// replace the least significant cube we just removed
+					tmp_add.add(least_significant_cube_pos);
+					// replace the canonical info from before
+					tmp_add.set_canonical_info(canonical_try_clone);
+					found_counts_by_n[tmp_add.n as usize] += 1;
+					// the initial delegator submits jobs for threads,
+					//   but only if the found polycube has n=spawn_n
+					if tmp_add.n == delegate_at_n {
+						// if the workers have fallen behind, wait here until
+						//   there is room in the queue
+						// once halted, the remaining jobs are sent back unevaluated
+						//   so they can be written to the resume file
+						let job_enc = tmp_add.find_canonical_info(None).enc;
+						if let Err(job_enc) = push_with_backpressure(submit_queue, job_enc, Some(atomic_halt)) {
+							push_response(response_queue, ThreadResponse{
+								job_complete: false,
+								results: None,
+								orig_polycube_enc: Some(job_enc),
+								final_polycube_encs: Vec::new(),
+								failure: None,
+								job_sec: None,
+								job_features: None });
+						}
+					} else if atomic_halt.load(Ordering::Relaxed) {
+						frontier.push(tmp_add.find_canonical_info(None).enc);
+					} else {
+						let futher_counts = extend_and_delegate(&tmp_add.copy(),
+								limit_n, delegate_at_n,
+								submit_queue, response_queue, atomic_halt, frontier);
+						for i in 1..limit_n+1 {
+							found_counts_by_n[i as usize] += futher_counts[i as usize];
+						}
+					}
+
+				// undo the temporary removal of the least significant cube,
+				//   but only if it's not the same as the cube we just tried
+				//   since we remove that one before going to the next iteration
+				//   of the loop
+				} else {
+					instrument!(c => c.pab_checks_failed += 1);
+					tmp_add.add(least_significant_cube_pos);
+				}
+			}
+
+			// revert creating P+A to try adding a cube at another position
+			tmp_add.remove(try_pos);
+			tmp_add.restore_start_cubes(&parent_start_cubes);
+		}
+	}
+	tmp_add.record_search_stats();
+	found_counts_by_n
+}
+
+// same as extend_single_thread, but
+//   - we report counts to the results queue
+//   - we occasionally check for a halt signal
+pub fn extend_as_worker(polycube: &mut Polycube, limit_n: u8,
+		submit_queue: &Arc<ArrayQueue<u128>>,
+		atomic_halt: &Arc<AtomicBool>,
+		rng: &mut ThreadRng,
+		save_final_polycubes: bool,
+		final_polycube_encs: &mut Vec<u128>) -> Option<[usize; 23]> {
+
+	let mut found_counts_by_n: [usize; 23] = [0; 23];
+
+	//found_counts_by_n[polycube.n as usize] += 1;
+
+	// we are done if we've reached the desired n,
+	//   which we need to stop at because we are doing
+	//   a depth-first recursive evaluation
+	if polycube.n == limit_n {
+		if save_final_polycubes {
+			final_polycube_encs.push(polycube.find_canonical_info(None).enc);
+		}
+		return Some(found_counts_by_n);
+	}
+
+	// keep a Set of all evaluated positions so we don't repeat them
+	let mut tried_pos: BTreeSet<isize> = BTreeSet::new();
+
+	let mut tried_canonicals: BTreeSet<u128> = BTreeSet::new();
+
+	let canonical_orig_enc: u128 = polycube.find_canonical_info(None).enc;
+	let symmetry_moves = polycube.find_symmetry_moves();
+	let parent_start_cubes = polycube.save_start_cubes();
+	let canonical_orig_enc_shifted = canonical_orig_enc << 6;
+	let orig_cube_value_counts = polycube.cube_value_counts;
+	let mut canonical_try: &CanonicalInfo;
+	let mut canonical_try_clone: CanonicalInfo;
+	let mut least_significant_cube_pos: isize;
+
+	let mut try_pos: isize;
+
+	// if halt has been signalled, abandon the evaluation
+	//   of this polycube
+	// since this function is run many many times by each process/thread,
+	//   we can greatly reduce use of AtomicBool.load() and increase per-
+	//   process CPU utilization
+	if rng.gen_range(0..1000) == 0 && atomic_halt.load(Ordering::Relaxed) {
+		return None;
+	}
+
+	// for each cube, for each direction, add a cube
+	// create a list to iterate over because the dict will change
+	//   during recursion within the loop
+	let original_positions: Vec<isize> = polycube.cube_info_by_pos.keys().cloned().collect();
+	// include all existing cubes' positions in the tried_pos set
+	tried_pos.extend(original_positions.iter());
+	for cube_pos in original_positions {
+		for direction_cost in DIRECTION_COSTS {
+			try_pos = cube_pos + direction_cost;
+
+			// skip if we've already tried this position
+			if !tried_pos.insert(try_pos) {
+				continue;
+			}
+			polycube.skip_symmetric_positions(&symmetry_moves, try_pos, &mut tried_pos);
+
+			instrument!(c => c.candidates_tried += 1);
+			// create P+A
+			polycube.add(try_pos);
+
+			// skip if we've already seen some p+1 with the same canonical representation
+			//   (comparing the bitwise int only)
+			// (the P+A that are the same as an earlier one because of
+			//   P's symmetries were already skipped above, so this only
+			//   catches the rest)
+			canonical_try = polycube.find_canonical_info_with_target(Some(try_pos), canonical_orig_enc_shifted);
+			if !tried_canonicals.insert(canonical_try.enc) {
+				instrument!(c => c.duplicate_canonicals += 1);
+				polycube.remove(try_pos);
+				polycube.restore_start_cubes(&parent_start_cubes);
+				continue;
+			}
+
+			// (P+A always encodes to more than P's encoding shifted over
+			//   one cube, since a neighbor of A gains a bit before A itself
+			//   is reached, so this is always found)
+			least_significant_cube_pos = canonical_try.least_significant_cube_pos.unwrap();
+
+			// if try_pos (cube A) is the least significant, then P+A-A==P and P+A is a new unique polycube
+			if least_significant_cube_pos == try_pos {
+				instrument!(c => c.least_significant_fast_path += 1);
+				found_counts_by_n[polycube.n as usize] += 1;
+				match extend_as_worker(polycube, limit_n,
+						submit_queue, atomic_halt, rng,
+						save_final_polycubes, final_polycube_encs) {
+					Some(futher_counts) => {
+						for i in 1..limit_n+1 {
+							found_counts_by_n[i as usize] += futher_counts[i as usize];
+						}
+					}
+					// if we have detected a halt while running the recursion,
+					//   we can continue to bubble the halt back up
+					None => {
+						return None;
+					}
+				}
+			} else {
+				canonical_try_clone = canonical_try.clone();
+				// remove the last of the ordered cubes (cube B) in P+A
+				polycube.remove(least_significant_cube_pos);
+				// if P+A-B has the same canonical representation as P, count P+A as a new unique polycube
+				//   and continue recursion into that P+A
+				// (the cube value counts are compared first since they rule
+				//   out many P+A-B without encoding them)
+				if polycube.matches_invariants(&orig_cube_value_counts)
+						&& polycube.find_canonical_enc_with_target(canonical_orig_enc) == canonical_orig_enc {
+					instrument!(c => c.pab_checks_passed += 1);
+					// replace the least significant cube we just removed
+					polycube.add(least_significant_cube_pos);
+					// replace the canonical info from before
+					polycube.set_canonical_info(canonical_try_clone);
+					found_counts_by_n[polycube.n as usize] += 1;
+					// continue recursion
+					match extend_as_worker(polycube, limit_n,
+							submit_queue, atomic_halt, rng, save_final_polycubes, final_polycube_encs) {
+						Some(futher_counts) => {
+							for i in 1..limit_n+1 {
+								found_counts_by_n[i as usize] += futher_counts[i as usize];
+							}
+						}
+						// if we have detected a halt while running the recursion,
+						//   we can continue to bubble the halt back up
+						None => {
+							return None;
+						}
+					}
+
+				// undo the temporary removal of the least significant cube,
+				//   but only if it's not the same as the cube we just tried
+				//   since we remove that one before going to the next iteration
+				//   of the loop
+				} else {
+					instrument!(c => c.pab_checks_failed += 1);
+					polycube.add(least_significant_cube_pos);
+				}
+			}
+
+			// revert creating P+A to try adding a cube at another position
+			polycube.remove(try_pos);
+			polycube.restore_start_cubes(&parent_start_cubes);
+		}
+	}
+	return Some(found_counts_by_n);
+}
+
+pub fn extend_single_thread(polycube: &mut Polycube, limit_n: u8, depth: usize) {
+	// since this is a valid polycube, increment the count
+	unsafe {
+		N_COUNTS[polycube.n as usize] += 1;
+	}
+
+	// we are done if we've reached the desired n,
+	//   which we need to stop at because we are doing
+	//   a depth-first recursive evaluation
+	if polycube.n == limit_n {
+		return;
+	}
+
+	// keep a Set of all evaluated positions so we don't repeat them
+	let mut tried_pos: BTreeSet<isize> = BTreeSet::new();
+
+	let mut tried_canonicals: BTreeSet<u128> = BTreeSet::new();
+
+	let canonical_orig_enc: u128 = polycube.find_canonical_info(None).enc;
+	let symmetry_moves = polycube.find_symmetry_moves();
+	let parent_start_cubes = polycube.save_start_cubes();
+	let canonical_orig_enc_shifted = canonical_orig_enc << 6;
+	let orig_cube_value_counts = polycube.cube_value_counts;
+	let mut canonical_try: &CanonicalInfo;
+	let mut canonical_try_clone: CanonicalInfo;
+	let mut least_significant_cube_pos: isize;
+
+	let mut try_pos: isize;
+
+	// for each cube, for each direction, add a cube
+	// create a list to iterate over because the dict will change
+	//   during recursion within the loop
+	let original_positions: Vec<isize> = polycube.cube_info_by_pos.keys().cloned().collect();
+	// include all existing cubes' positions in the tried_pos set
+	tried_pos.extend(original_positions.iter());
+	for cube_pos in original_positions {
+		for direction_cost in DIRECTION_COSTS {
+			try_pos = cube_pos + direction_cost;
+
+			// skip if we've already tried this position
+			if !tried_pos.insert(try_pos) {
+				continue;
+			}
+			polycube.skip_symmetric_positions(&symmetry_moves, try_pos, &mut tried_pos);
+
+			instrument!(c => c.candidates_tried += 1);
+			// create P+A
+			polycube.add(try_pos);
+
+			// skip if we've already seen some P+A with the same canonical representation
+			//   (comparing the bitwise int only)
+			canonical_try = polycube.find_canonical_info_with_target(Some(try_pos), canonical_orig_enc_shifted);
+			if !tried_canonicals.insert(canonical_try.enc) {
+				instrument!(c => c.duplicate_canonicals += 1);
+				polycube.remove(try_pos);
+				polycube.restore_start_cubes(&parent_start_cubes);
+				continue;
+			}
+
+			// (P+A always encodes to more than P's encoding shifted over
+			//   one cube, since a neighbor of A gains a bit before A itself
+			//   is reached, so this is always found)
+			least_significant_cube_pos = canonical_try.least_significant_cube_pos.unwrap();
+
+			// if try_pos (cube A) is the least significant, then P+A-A==P and P+A is a new unique polycube
+			if least_significant_cube_pos == try_pos {
+				instrument!(c => c.least_significant_fast_path += 1);
+				extend_single_thread(polycube,  limit_n, depth+1);
+			} else {
+				canonical_try_clone = canonical_try.clone();
+				// remove the last of the ordered cubes (cube B) in P+A
+				polycube.remove(least_significant_cube_pos);
+				// if P+A-B has the same canonical representation as P, count it as a new unique polycube
+				//   and continue recursion into that P+A
+				// (the cube value counts are compared first since they rule
+				//   out many P+A-B without encoding them)
+				if polycube.matches_invariants(&orig_cube_value_counts)
+						&& polycube.find_canonical_enc_with_target(canonical_orig_enc) == canonical_orig_enc {
+					instrument!(c => c.pab_checks_passed += 1);
+					// replace the least significant cube we just removed
+					polycube.add(least_significant_cube_pos);
+					// replace the canonical info from before
+					polycube.set_canonical_info(canonical_try_clone);
+					// continue recursion
+					extend_single_thread(polycube,  limit_n, depth+1);
+
+				// undo the temporary removal of the least significant cube,
+				//   but only if it's not the same as the cube we just tried
+				//   since we remove that one before going to the next iteration
+				//   of the loop
+				} else {
+					instrument!(c => c.pab_checks_failed += 1);
+					polycube.add(least_significant_cube_pos);
+				}
+			}
+
+			// revert creating P+A to try adding a cube at another position
+			polycube.remove(try_pos);
+			polycube.restore_start_cubes(&parent_start_cubes);
+		}
+	}
+}
+
+// the positions where a cube can be added to make each of the
+//   polycube's children in the canonical extension tree, by the same
+//   rule as extend_single_thread(): P+A is a child of P if removing
+//   the least significant cube (B) of P+A leaves P
+// (one position is given for each distinct child)
+pub fn canonical_children(polycube: &mut Polycube) -> Vec<isize> {
+	let mut children: Vec<isize> = Vec::new();
+	let mut tried_canonicals: BTreeSet<u128> = BTreeSet::new();
+	let canonical_orig_enc: u128 = polycube.find_canonical_info(None).enc;
+	let symmetry_moves = polycube.find_symmetry_moves();
+	let parent_start_cubes = polycube.save_start_cubes();
+	let orig_cube_value_counts = polycube.cube_value_counts;
+	let original_positions: Vec<isize> = polycube.cube_info_by_pos.keys().cloned().collect();
+	let mut tried_pos: BTreeSet<isize> = original_positions.iter().cloned().collect();
+	for cube_pos in original_positions {
+		for direction_cost in DIRECTION_COSTS {
+			let try_pos = cube_pos + direction_cost;
+			if !tried_pos.insert(try_pos) {
+				continue;
+			}
+			polycube.skip_symmetric_positions(&symmetry_moves, try_pos, &mut tried_pos);
+			polycube.add(try_pos);
+			let canonical_try = polycube.find_canonical_info_with_target(Some(try_pos), canonical_orig_enc << 6);
+			let canonical_try_enc = canonical_try.enc;
+			let least_significant_cube_pos = canonical_try.least_significant_cube_pos.unwrap();
+			if tried_canonicals.insert(canonical_try_enc) {
+				if least_significant_cube_pos == try_pos {
+					children.push(try_pos);
+				} else {
+					polycube.remove(least_significant_cube_pos);
+					if polycube.matches_invariants(&orig_cube_value_counts)
+							&& polycube.find_canonical_enc_with_target(canonical_orig_enc) == canonical_orig_enc {
+						children.push(try_pos);
+					}
+					polycube.add(least_significant_cube_pos);
+				}
+			}
+			polycube.remove(try_pos);
+			polycube.restore_start_cubes(&parent_start_cubes);
+		}
+	}
+	children
+}
+
+// the canonical encodings of the polycube's children in the canonical
+//   extension tree, in increasing order, which is the order used for
+//   ranking polycubes
+pub fn sorted_canonical_children(polycube: &mut Polycube) -> Vec<u128> {
+	let parent_start_cubes = polycube.save_start_cubes();
+	let mut child_encs: Vec<u128> = canonical_children(polycube).into_iter().map(|try_pos| {
+		polycube.add(try_pos);
+		let child_enc = polycube.find_canonical_info(None).enc;
+		polycube.remove(try_pos);
+		polycube.restore_start_cubes(&parent_start_cubes);
+		child_enc
+	}).collect();
+	child_encs.sort();
+	child_encs
+}
+
+// the number of polycubes of size n in the subtree of the canonical
+//   extension tree rooted at the given (canonical) polycube, found by
+//   enumerating them
+pub fn subtree_count(polycube_enc: u128, n: u8) -> u128 {
+	let mut polycube = Polycube::new(false);
+	polycube.rebuild_from_encoding(polycube_enc);
+	if polycube.n >= n {
+		return if polycube.n == n { 1 } else { 0 };
+	}
+	// extend_as_worker() doesn't submit jobs, so this is unused
+	let submit_queue: Arc<ArrayQueue<u128>> = Arc::new(ArrayQueue::new(1));
+	let results = extend_as_worker(&mut polycube, n, &submit_queue, &Arc::new(AtomicBool::new(false)), &mut thread_rng(), false, &mut Vec::new()).unwrap();
+	results[n as usize] as u128
+}
+
+// the number of polycubes of each size up to n in the subtree of the
+//   canonical extension tree rooted at the given polycube (including
+//   the polycube itself)
+// with threads, the subtree is split at size spawn_n, and the polycubes
+//   of that size are handed out to the threads as they finish
+pub fn subtree_counts(polycube_enc: u128, n: u8, threads: usize, spawn_n: u8) -> [usize; MAX_N] {
+	let mut polycube = Polycube::new(false);
+	polycube.rebuild_from_encoding(polycube_enc);
+	// extend_as_worker() doesn't submit jobs, so this is unused
+	let submit_queue: Arc<ArrayQueue<u128>> = Arc::new(ArrayQueue::new(1));
+	let atomic_halt = Arc::new(AtomicBool::new(false));
+	let mut counts = [0; MAX_N];
+	counts[polycube.n as usize] = 1;
+	if threads == 0 || spawn_n <= polycube.n || spawn_n >= n {
+		let results = extend_as_worker(&mut polycube, n, &submit_queue, &atomic_halt, &mut thread_rng(), false, &mut Vec::new()).unwrap();
+		for i in polycube.n as usize + 1..n as usize + 1 {
+			counts[i] += results[i];
+		}
+		return counts;
+	}
+	let mut job_encs: Vec<u128> = Vec::new();
+	let results = extend_as_worker(&mut polycube, spawn_n, &submit_queue, &atomic_halt, &mut thread_rng(), true, &mut job_encs).unwrap();
+	for i in polycube.n as usize + 1..spawn_n as usize + 1 {
+		counts[i] += results[i];
+	}
+	let job_encs = Arc::new(job_encs);
+	let next_job = Arc::new(AtomicUsize::new(0));
+	let handles: Vec<JoinHandle<[usize; MAX_N]>> = (0..threads).map(|_| {
+		let job_encs = job_encs.clone();
+		let next_job = next_job.clone();
+		let submit_queue = submit_queue.clone();
+		let atomic_halt = atomic_halt.clone();
+		thread::spawn(move || {
+			let mut rng = thread_rng();
+			let mut thread_counts = [0; MAX_N];
+			let mut job = Polycube::new(false);
+			while let Some(job_enc) = job_encs.get(next_job.fetch_add(1, Ordering::Relaxed)) {
+				job.rebuild_from_encoding(*job_enc);
+				let results = extend_as_worker(&mut job, n, &submit_queue, &atomic_halt, &mut rng, false, &mut Vec::new()).unwrap();
+				for i in spawn_n as usize + 1..n as usize + 1 {
+					thread_counts[i] += results[i];
+				}
+				job = Polycube::new(false);
+			}
+			thread_counts
+		})
+	}).collect();
+	for handle in handles {
+		let thread_counts = handle.join().unwrap();
+		for i in spawn_n as usize + 1..n as usize + 1 {
+			counts[i] += thread_counts[i];
+		}
+	}
+	counts
+}
+
+// the subtree counts of the polycubes up to this size, by polycube
+//   and n, kept for rank() and unrank() (there are 8,152 polycubes up
+//   to size 8)
+const SUBTREE_COUNT_CACHE_N: u8 = 8;
+static SUBTREE_COUNT_CACHE: Mutex<BTreeMap<(u128, u8), u128>> = Mutex::new(BTreeMap::new());
+
+// subtree_count(), kept for polycubes up to size SUBTREE_COUNT_CACHE_N,
+//   which are counted through their children, so that each subtree
+//   below that size is only ever counted once
+pub fn cached_subtree_count(polycube_enc: u128, n: u8) -> u128 {
+	let mut polycube = Polycube::new(false);
+	polycube.rebuild_from_encoding(polycube_enc);
+	if polycube.n > SUBTREE_COUNT_CACHE_N || polycube.n >= n {
+		return subtree_count(polycube_enc, n);
+	}
+	if let Some(count) = SUBTREE_COUNT_CACHE.lock().unwrap().get(&(polycube_enc, n)) {
+		return *count;
+	}
+	let count = if polycube.n == SUBTREE_COUNT_CACHE_N {
+		subtree_count(polycube_enc, n)
+	} else {
+		sorted_canonical_children(&mut polycube).into_iter().map(|child_enc| cached_subtree_count(child_enc, n)).sum()
+	};
+	SUBTREE_COUNT_CACHE.lock().unwrap().insert((polycube_enc, n), count);
+	count
+}
+
+// the rank of a polycube among all the polycubes of its size, in the
+//   order they're reached by a depth-first walk of the canonical
+//   extension tree, taking each polycube's children in increasing
+//   order of their canonical encodings
+// this doesn't depend on the canonicalizer or the number of threads,
+//   so a polycube's size and rank identify it, but finding them means
+//   counting the polycubes in the subtrees before it, which can take
+//   as long as enumerating all the polycubes of its size
+// the counts of the subtrees near the root are kept, though (see
+//   cached_subtree_count), so after the first, ranks of the same size
+//   only count the polycubes in the small subtrees near their own
+pub fn rank(polycube: &Polycube) -> u128 {
+	let n = polycube.n;
+	let mut polycube = polycube.copy();
+	// the path down from the monocube, leaving out the monocube
+	let mut ancestry: Vec<u128> = vec![polycube.find_canonical_info(None).enc];
+	ancestry.extend(polycube.ancestry().iter_mut().map(|ancestor| ancestor.find_canonical_info(None).enc));
+	ancestry.pop();
+	let mut rank: u128 = 0;
+	let mut parent = Polycube::new(true);
+	while let Some(child_enc) = ancestry.pop() {
+		for sibling_enc in sorted_canonical_children(&mut parent) {
+			if sibling_enc == child_enc {
+				break;
+			}
+			rank += cached_subtree_count(sibling_enc, n);
+		}
+		parent = Polycube::new(false);
+		parent.rebuild_from_encoding(child_enc);
+	}
+	rank
+}
+
+// the polycube of size n with the given rank (see rank()), if there
+//   are more than that many polycubes of size n
+// (this keeps the same subtree counts as rank())
+pub fn unrank(n: u8, mut k: u128) -> Option<Polycube> {
+	let mut polycube = Polycube::new(true);
+	while polycube.n < n {
+		let mut next: Option<u128> = None;
+		for child_enc in sorted_canonical_children(&mut polycube) {
+			let count = cached_subtree_count(child_enc, n);
+			if k < count {
+				next = Some(child_enc);
+				break;
+			}
+			k -= count;
+		}
+		polycube = Polycube::new(false);
+		polycube.rebuild_from_encoding(next?);
+	}
+	if k == 0 { Some(polycube) } else { None }
+}
+
+// take one random walk down the canonical extension tree from the
+//   single cube toward size limit_n, choosing each child uniformly,
+//   setting Knuth's estimate of the count at each size along the way:
+//   the product of the numbers of children seen above it (or 0 past
+//   a dead end)
+// the polycube reached is returned, and the chance of reaching it
+//   was 1 over its estimate
+pub fn knuth_walk(limit_n: u8, rng: &mut impl Rng, estimates: &mut [f64; MAX_N]) -> Polycube {
+	let mut polycube = Polycube::new(true);
+	let mut estimate: f64 = 1.0;
+	for n in 1..limit_n+1 {
+		estimates[n as usize] = estimate;
+		if n == limit_n || estimate == 0.0 {
+			continue;
+		}
+		let children = canonical_children(&mut polycube);
+		estimate *= children.len() as f64;
+		if let Some(try_pos) = children.choose(rng) {
+			polycube.add(*try_pos);
+		}
+	}
+	polycube
+}
+
+// pick a random canonical polycube of size n by importance
+//   resampling: a Knuth walk reaches each polycube with a chance of 1
+//   over its estimate (see knuth_walk), so one of the given number of
+//   walks is picked with a chance in proportion to its estimate, which
+//   corrects for that, so that every polycube is picked with the same
+//   chance as the number of walks grows
+// the error shrinks in proportion to the number of walks, and grows
+//   with the spread of the estimates, which widens with n, so this
+//   also returns the effective number of walks, (sum of estimates)^2
+//   over (sum of squared estimates): how many walks reaching uniformly
+//   random polycubes the weighted walks are worth
+pub fn sample_polycube(n: u8, walks: usize, rng: &mut impl Rng) -> (u128, f64) {
+	let mut estimates = [0.0; MAX_N];
+	let mut picked: Option<Polycube> = None;
+	let (mut sum, mut sum_squares): (f64, f64) = (0.0, 0.0);
+	let mut walk: usize = 0;
+	// (a walk that reaches a dead end has an estimate of 0, so keep
+	//   going until at least one reaches n)
+	while walk < walks || picked.is_none() {
+		walk += 1;
+		let polycube = knuth_walk(n, rng, &mut estimates);
+		let estimate = estimates[n as usize];
+		if estimate == 0.0 {
+			continue;
+		}
+		sum += estimate;
+		sum_squares += estimate * estimate;
+		// (this keeps each walk so far with a chance of its estimate
+		//   over the sum, without holding on to them all)
+		if rng.gen::<f64>() * sum < estimate {
+			picked = Some(polycube);
+		}
+	}
+	(picked.unwrap().find_canonical_info(None).enc, sum * sum / sum_squares)
+}
+
+// the given number of random polycubes of size n, with their
+//   effective numbers of walks (see sample_polycube), split among the
+//   threads, where each sample has its own random number generator
+//   seeded from the seed and the sample's index, so the same seed
+//   gives the same samples regardless of the number of threads
+pub fn sample_polycubes(n: u8, count: usize, walks: usize, seed: u64, threads: usize) -> Vec<(u128, f64)> {
+	let handles: Vec<JoinHandle<Vec<(u128, f64)>>> = (0..threads).map(|i| {
+		thread::spawn(move || {
+			(i..count).step_by(threads).map(|sample_index| {
+				let mut rng = StdRng::seed_from_u64(seed.wrapping_add(sample_index as u64));
+				sample_polycube(n, walks, &mut rng)
+			}).collect()
+		})
+	}).collect();
+	let thread_samples: Vec<Vec<(u128, f64)>> = handles.into_iter().map(|handle| handle.join().unwrap()).collect();
+	// interleave the threads' samples back into the order of their indices
+	(0..count).map(|sample_index| thread_samples[sample_index % threads][sample_index / threads]).collect()
+}
+
+// the chance of a standard normal variable being above z
+pub fn normal_upper_tail(z: f64) -> f64 {
+	// from Abramowitz and Stegun 7.1.26, for erfc(x) with x >= 0
+	let x = z.abs() / std::f64::consts::SQRT_2;
+	let t = 1.0 / (1.0 + 0.3275911 * x);
+	let erfc = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429)))) * (-x * x).exp();
+	if z >= 0.0 { erfc / 2.0 } else { 1.0 - erfc / 2.0 }
+}
+
+// draw random polycubes of size n and compare how often each was
+//   drawn against every polycube of size n found by enumerating
+//   them, with a chi-square test (whose p-value is found with the
+//   Wilson-Hilferty approximation)
+// returns the p-value, or None if any sample wasn't a polycube of size n
+pub fn check_sampler_uniformity(n: u8, count: usize, walks: usize, seed: u64, threads: usize) -> Option<f64> {
+	let submit_queue: Arc<ArrayQueue<u128>> = Arc::new(ArrayQueue::new(1));
+	let mut all_encs: Vec<u128> = Vec::new();
+	extend_as_worker(&mut Polycube::new(true), n, &submit_queue, &Arc::new(AtomicBool::new(false)), &mut thread_rng(), true, &mut all_encs);
+	let mut observed: BTreeMap<u128, usize> = all_encs.iter().map(|polycube_enc| (*polycube_enc, 0)).collect();
+	println!("enumerated {} polycubes of size {}, drawing {} samples...", observed.len(), n, count);
+	let samples = sample_polycubes(n, count, walks, seed, threads);
+	for (polycube_enc, _effective_walks) in samples.iter() {
+		match observed.get_mut(polycube_enc) {
+			Some(times) => *times += 1,
+			None => {
+				println!("error: sampled [{}], which isn't one of the enumerated polycubes", encoding_to_str(*polycube_enc));
+				return None;
+			}
+		}
+	}
+	let expected = samples.len() as f64 / observed.len() as f64;
+	let chi_square: f64 = observed.values().map(|times| (*times as f64 - expected).powi(2) / expected).sum();
+	let df = (observed.len() - 1) as f64;
+	let z = ((chi_square / df).powf(1.0 / 3.0) - (1.0 - 2.0 / (9.0 * df))) / (2.0 / (9.0 * df)).sqrt();
+	let (fewest, most) = (observed.values().min().unwrap(), observed.values().max().unwrap());
+	println!("each polycube was drawn {} to {} times ({:.1} expected), chi-square={:.1} with {} degrees of freedom",
+		fewest, most, expected, chi_square, df);
+	Some(normal_upper_tail(z))
+}
+
+// the estimated count, with its standard error, at each size up
+//   to limit_n from the given number of random walks, which are
+//   split among the threads
+pub fn knuth_estimate(limit_n: u8, samples: usize, threads: usize) -> [StratumSums; MAX_N] {
+	let handles: Vec<JoinHandle<[StratumSums; MAX_N]>> = (0..threads).map(|i| {
+		let thread_samples = samples / threads + if i < samples % threads { 1 } else { 0 };
+		thread::spawn(move || {
+			let mut rng = thread_rng();
+			let mut sums = [StratumSums::default(); MAX_N];
+			let mut estimates = [0.0; MAX_N];
+			for _ in 0..thread_samples {
+				knuth_walk(limit_n, &mut rng, &mut estimates);
+				for n in 1..limit_n as usize + 1 {
+					sums[n].add(estimates[n]);
+				}
+			}
+			sums
+		})
+	}).collect();
+	let mut sums = [StratumSums::default(); MAX_N];
+	for handle in handles {
+		let thread_sums = handle.join().unwrap();
+		for n in 1..limit_n as usize + 1 {
+			sums[n].merge(&thread_sums[n]);
+		}
+	}
+	sums
+}
+
+// the jobs to save are those returned by the workers, plus any
+//   jobs remaining in the job file we were fed from (which are
+//   streamed from the old file into the new one)
+// write a file through a temporary file beside it, which is fsynced
+//   and then renamed into place, so a crash part way through leaves
+//   either the old file or the new one, but never part of the new one
+pub fn write_file_atomically<F>(path: &Path, description: &str, write_contents: F)
+		where F: FnOnce(&mut BufWriter<File>) -> std::io::Result<()> {
+	let mut tmp_path = path.as_os_str().to_owned();
+	tmp_path.push(".tmp");
+	let tmp_path = PathBuf::from(tmp_path);
+	let result = File::create(&tmp_path).and_then(|file_buf| {
+		let mut buf = BufWriter::new(file_buf);
+		write_contents(&mut buf)?;
+		let file_buf = buf.into_inner().map_err(|err| err.into_error())?;
+		file_buf.sync_all()?;
+		std::fs::rename(&tmp_path, path)?;
+		sync_parent_dir(path)
+	});
+	if let Err(err) = result {
+		report_error(format!("writing to {} [{}]: {}", description, path.to_string_lossy(), err));
+		// if we have an error writing the file, there's no
+		//   point in continuing
+		exit(EXIT_ERROR);
+	}
+}
+
+// the rename of a new file (or the creation of one) only survives
+//   a crash once the directory holding it has been synced too
+#[cfg(unix)]
+pub fn sync_parent_dir(path: &Path) -> std::io::Result<()> {
+	match path.parent() {
+		Some(dir) if !dir.as_os_str().is_empty() => File::open(dir)?.sync_all(),
+		_ => File::open(".")?.sync_all()
+	}
+}
+
+// directories can't be opened as files on windows, where the rename
+//   is flushed along with the file
+#[cfg(not(unix))]
+pub fn sync_parent_dir(_path: &Path) -> std::io::Result<()> {
+	Ok(())
+}
+
+// how far a halted run got, which is written in its resume file
+pub struct RunProgress<'a> {
+	// including the time spent on earlier runs, when resuming
+	pub elapsed_sec: f64,
+	pub completed_jobs: usize,
+	pub total_jobs: usize,
+	pub count_status: [CountStatus; MAX_N],
+	pub cost_model: JobCostModel,
+	pub count_projection: &'a CountProjection,
+	pub projected_count: Option<ProjectedCount>,
+	pub config: &'a RunConfig,
+	pub frontier: &'a [u128]
+}
+
+// returns the path of the resume file
+pub fn write_resume_file(n: u8, spawn_n: u8, polycubes_to_write_to_disk: Vec<u128>, unsubmitted_jobs: Option<JobFileReader>, progress: &RunProgress) -> PathBuf {
+	let timestamp = Local::now().to_rfc3339().replace('-', "").replace(':', "");
+	let filename = format!("halt-n{}-{}.txt", n, &timestamp[0..15]);
+	let resume_file_path = create_work_file(progress.config.work_dir.as_deref(), filename.as_str());
+	let job_count = polycubes_to_write_to_disk.len() + unsubmitted_jobs.as_ref().map_or(0, |reader| reader.remaining());
+	if !json_events() {
+		println!("writing {} polycubes to [{}]...", job_count, resume_file_path.to_str().unwrap());
+	}
+	// i am getting strange repeated/missing characters in the .gz file,
+	//   so i am trying a lower compression level
+	//let mut gz = GzEncoder::new(&mut file_buf, Compression::best());
+	// still getting errors at the default compression level
+	//let mut gz = GzEncoder::new(&mut file_buf, Compression::default());
+	// still getting errors at the fast compression level
+	//let mut gz = GzEncoder::new(&mut file_buf, Compression::fast());
+	// this works, but there's no point in creating a .gz file without compression
+	//let mut gz = GzEncoder::new(&mut file_buf, Compression::none());
+	write_file_atomically(&resume_file_path, "resume file", |gz| {
+		gz.write_all(format!("{}\n{}\n{}\n", n, spawn_n, progress.elapsed_sec).as_bytes())?;
+		let counts = unsafe { N_COUNTS };
+		gz.write_all(format!("{}\n", counts.iter().enumerate().map(|(i,count)| format!("{}={}", i, count)).collect::<Vec<String>>().join(",")).as_bytes())?;
+		// the job count lets a resumed run report its progress
+		//   without reading through the whole file first
+		gz.write_all(format!("job_count={}\n", job_count).as_bytes())?;
+		// so the completed and remaining jobs can be checked
+		//   against the jobs the run started with
+		gz.write_all(format!("completed_jobs={}\ntotal_jobs={}\n", progress.completed_jobs, progress.total_jobs).as_bytes())?;
+		// so a resumed run's estimates start out calibrated
+		gz.write_all(format!("cost_model={}\n", progress.cost_model.to_header_value()).as_bytes())?;
+		// likewise for the projection of the final count, which is also
+		//   written out, for reference
+		gz.write_all(format!("projection_strata={}\n", progress.count_projection.to_header_value()).as_bytes())?;
+		gz.write_all(format!("projection_ratio={}\n", progress.count_projection.by_probe.to_header_value()).as_bytes())?;
+		if let Some(projected_count) = progress.projected_count {
+			gz.write_all(format!("projected_count={}:{}:{}\n", projected_count.value, projected_count.low, projected_count.high).as_bytes())?;
+		}
+		// so that resuming needs nothing but this file
+		gz.write_all(format!("config={}\n", progress.config.to_header_value()).as_bytes())?;
+		// the subtrees the initial delegator was halted before enumerating
+		if !progress.frontier.is_empty() {
+			gz.write_all(format!("frontier={}\n", progress.frontier.iter().map(|polycube_enc| polycube_enc.to_string()).collect::<Vec<String>>().join(",")).as_bytes())?;
+		}
+		// whether each count so far is exact, partial or missing
+		gz.write_all(format!("count_status={}\n", (1..n+1).map(|i| format!("{}:{}", i, progress.count_status[i as usize].name())).collect::<Vec<String>>().join(",")).as_bytes())?;
+		// one canonical encoding per line, which is what
+		//   JobFileReader expects
+		let mut written: usize = 0;
+		for polycube_enc in polycubes_to_write_to_disk.iter().copied().chain(unsubmitted_jobs.into_iter().flatten()) {
+			gz.write_all(format!("{}\n", polycube_enc).as_bytes())?;
+			written += 1;
+		}
+		gz.write_all(format!("{} {}\n", END_MARKER, written).as_bytes())
+	});
+	if json_events() {
+		emit_event("checkpoint", serde_json::json!({
+			"file": resume_file_path.to_string_lossy(),
+			"jobs": job_count,
+			"completed_jobs": progress.completed_jobs,
+			"total_jobs": progress.total_jobs
+		}));
+	}
+	resume_file_path
+}
+
+// jobs that panicked on every attempt are written here, one per line
+//   as the base94 canonical encoding followed by the panic message,
+//   so they can be looked into without digging through the output
+pub fn write_quarantine_file(n: u8, work_dir: Option<&Path>, quarantined_jobs: &Vec<(u128, String)>) {
+	let timestamp = Local::now().to_rfc3339().replace(['-', ':'], "");
+	let filename = format!("quarantine-n{}-{}.txt", n, &timestamp[0..15]);
+	let quarantine_file_path = create_work_file(work_dir, filename.as_str());
+	report_warning(format!("{} jobs failed and were not counted, writing them to [{}]...", quarantined_jobs.len(), quarantine_file_path.to_str().unwrap()));
+	write_file_atomically(&quarantine_file_path, "quarantine file", |buf| {
+		for (polycube_enc, message) in quarantined_jobs {
+			// keep each job on a single line
+			let message = message.replace('\n', " ");
+			buf.write_all(format!("{} {}\n", encoding_to_str(*polycube_enc), message).as_bytes())?;
+		}
+		Ok(())
+	});
+}
+
+// the two kinds of files that jobs can be read from
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum JobFileFormat {
+	// a "resume file" contains partial results from a halted run,
+	//   followed by one decimal canonical encoding per line
+	Resume,
+	// a polycubes file contains n on its first line, followed by
+	//   one base94 canonical encoding per line
+	Polycubes,
+	// a polycubes file written before batches were closed with
+	//   trailers, which is read to its end without checking that it
+	//   wasn't cut short
+	LegacyPolycubes
+}
+
+// everything in a job file that comes before the jobs themselves
+//   (a polycubes file only has n)
+pub struct JobFileHeader {
+	pub n: u8,
+	pub spawn_n: u8,
+	pub previous_total_elapsed_sec: f64,
+	pub n_counts: BTreeMap<u8, usize>,
+	// the number of jobs in the file, if it's written in the header
+	pub job_count: Option<usize>,
+	// the number of jobs the halted run had completed, and the
+	//   number it started with, if written in the header
+	pub completed_jobs: Option<usize>,
+	pub total_jobs: Option<usize>,
+	// whether each count is exact, partial or missing, if written in the header
+	pub count_status: BTreeMap<u8, CountStatus>,
+	// the job cost model as calibrated by the halted run, if written in the header
+	pub cost_model: Option<JobCostModel>,
+	// the completed jobs' counts by job features, for projecting
+	//   the final count, if written in the header
+	pub projection_strata: Option<BTreeMap<JobFeatures, StratumSums>>,
+	// the completed jobs' counts against their probes, likewise
+	pub projection_ratio: Option<RatioEstimator>,
+	// the halted run's settings, if written in the header
+	pub config: Option<RunConfig>,
+	// the polycubes smaller than spawn_n that the halted run's initial
+	//   delegator counted but didn't enumerate the subtrees of, so the
+	//   jobs under them are still to be found
+	pub frontier: Vec<u128>
+}
+
+// reads the jobs in a resume file or polycubes file one line at
+//   a time, so that even a job file with tens of millions of
+//   polycubes can be fed to the workers without loading it all
+//   into memory first
+pub struct JobFileReader {
+	pub header: JobFileHeader,
+	// the total number of jobs in the file, taken from the header if
+	//   present, otherwise found by reading through the file once
+	pub job_count: usize,
+	// the number of jobs returned so far
+	pub jobs_read: usize,
+	format: JobFileFormat,
+	file_err_msg: String,
+	buf: BufReader<GzDecoder<File>>,
+	line: String,
+	// a job that has been read but not yet returned
+	pending: Option<u128>,
+	// the number of jobs read since the last trailer
+	batch_len: usize,
+	trailer_seen: bool,
+	// set once the trailer closing a resume file has been read
+	ended: bool
+}
+
+impl JobFileReader {
+	pub fn open(job_file_path: &PathBuf, format: JobFileFormat) -> JobFileReader {
+		let mut reader = JobFileReader::open_without_count(job_file_path, format);
+		// a resume file's header has its job count, which is checked
+		//   against the trailer once the jobs have been streamed (and a
+		//   file that was cut short fails there, for lack of a trailer)
+		// otherwise the jobs are counted by reading through the whole
+		//   file once before any job is handed out (a polycubes file is
+		//   appended to, so it can't carry a job count in its header)
+		reader.job_count = match reader.header.job_count {
+			Some(job_count) => job_count,
+			None => JobFileReader::open_without_count(job_file_path, format).count()
+		};
+		reader
+	}
+
+	// read just the header, without checking the rest of the file
+	pub fn open_header(job_file_path: &PathBuf, format: JobFileFormat) -> JobFileHeader {
+		JobFileReader::open_without_count(job_file_path, format).header
+	}
+
+	fn open_without_count(job_file_path: &PathBuf, format: JobFileFormat) -> JobFileReader {
+		let file_err_msg: String = format!("reading job file [{}]", job_file_path.to_string_lossy());
+		let f = File::open(job_file_path).unwrap_or_else(|err| {
+			report_error(format!("{}: {}", file_err_msg, err));
+			exit(EXIT_ERROR);
+		});
+		let file_extension = job_file_path.extension().unwrap();
+		if file_extension != "gz" {
+			report_error(format!("unsupported job file extension of [{:?}]", job_file_path));
+			exit(EXIT_ERROR);
+		}
+		let mut reader = JobFileReader {
+			header: JobFileHeader {
+				n: 0,
+				spawn_n: 0,
+				previous_total_elapsed_sec: 0.0,
+				n_counts: BTreeMap::new(),
+				job_count: None,
+				completed_jobs: None,
+				total_jobs: None,
+				count_status: BTreeMap::new(),
+				cost_model: None,
+				projection_strata: None,
+				projection_ratio: None,
+				config: None,
+				frontier: Vec::new()
+			},
+			job_count: 0,
+			jobs_read: 0,
+			format,
+			file_err_msg,
+			buf: BufReader::new(GzDecoder::new(f)),
+			line: String::new(),
+			pending: None,
+			batch_len: 0,
+			trailer_seen: false,
+			ended: false
+		};
+		reader.read_header();
+		reader
+	}
+
+	// read the next non-empty line into self.line, returning false
+	//   at the end of the file or at the trailer that ends a resume
+	//   file (a polycubes file carries on after each batch's trailer)
+	fn read_line(&mut self) -> bool {
+		if self.ended {
+			return false;
+		}
+		loop {
+			self.line.clear();
+			let len = self.buf.read_line(&mut self.line).unwrap_or_else(|err| {
+				report_error(format!("{}: {}", self.file_err_msg, err));
+				exit(EXIT_ERROR);
+			});
+			if len == 0 {
+				if self.format != JobFileFormat::LegacyPolycubes && (!self.trailer_seen || self.batch_len > 0) {
+					report_error(format!("{}: no \"{}\" trailer after the last {} jobs, so the file may have been cut short",
+						self.file_err_msg, END_MARKER, self.batch_len));
+					exit(EXIT_ERROR);
+				}
+				return false;
+			}
+			let trimmed_len = self.line.trim_end().len();
+			self.line.truncate(trimmed_len);
+			if let Some(count) = self.line.strip_prefix(END_MARKER) {
+				self.check_trailer(count.trim().to_string());
+				if self.format == JobFileFormat::Resume {
+					self.ended = true;
+					return false;
+				}
+				continue;
+			}
+			if !self.line.is_empty() {
+				return true;
+			}
+		}
+	}
+
+	// the trailer holds the number of jobs since the previous trailer
+	// resume files written before the count was added to the trailer
+	//   are checked against the job_count in their header, if any
+	fn check_trailer(&mut self, count: String) {
+		let valid = if count.is_empty() && self.format == JobFileFormat::Resume {
+			self.header.job_count.is_none_or(|job_count| job_count == self.batch_len)
+		} else {
+			count.parse::<usize>() == Ok(self.batch_len)
+		};
+		if !valid {
+			report_error(format!("{}: invalid trailer [{}] after {} jobs", self.file_err_msg, self.line, self.batch_len));
+			exit(EXIT_ERROR);
+		}
+		// (a resume file has a single batch, which holds all its jobs)
+		if self.format == JobFileFormat::Resume {
+			if let Some(job_count) = self.header.job_count.filter(|job_count| *job_count != self.batch_len) {
+				report_error(format!("{}: header has job_count={}, but the file contains {} jobs", self.file_err_msg, job_count, self.batch_len));
+				exit(EXIT_ERROR);
+			}
+		}
+		self.batch_len = 0;
+		self.trailer_seen = true;
+	}
+
+	fn parse_job_line(&mut self) -> u128 {
+		self.batch_len += 1;
+		match self.format {
+			JobFileFormat::Resume => match self.line.parse() {
+				Ok(polycube_enc) => polycube_enc,
+				Err(_) => {
+					report_error(format!("{}: invalid job line [{}]", self.file_err_msg, self.line));
+					exit(EXIT_ERROR);
+				}
+			},
+			JobFileFormat::Polycubes | JobFileFormat::LegacyPolycubes => str_to_encoding(self.line.as_str())
+		}
+	}
+
+	fn read_header(&mut self) {
+		// first line is n (for both kinds of file)
+		if !self.read_line() {
+			return;
+		}
+		self.header.n = self.line.parse().unwrap();
+		if self.format != JobFileFormat::Resume {
+			return;
+		}
+		// second line is the spawn_n
+		if !self.read_line() {
+			return;
+		}
+		self.header.spawn_n = self.line.parse().unwrap();
+		// third line is the previous_total_elapsed_sec
+		if !self.read_line() {
+			return;
+		}
+		self.header.previous_total_elapsed_sec = self.line.parse().unwrap();
+		// fourth line is the n_counts: "1=1,2=1,3=2,..."
+		if !self.read_line() {
+			return;
+		}
+		for item in self.line.split(',').map(|item| item.split_once('=')) {
+			let (n, count) = item.unwrap();
+			self.header.n_counts.insert(n.parse().unwrap(), count.parse().unwrap());
+		}
+		// any "key=value" lines after that are optional header
+		//   fields, and the first line without an '=' is the first job
+		while self.read_line() {
+			match self.line.split_once('=') {
+				Some(("job_count", job_count)) => {
+					self.header.job_count = Some(job_count.parse().unwrap());
+				}
+				Some(("completed_jobs", completed_jobs)) => {
+					self.header.completed_jobs = Some(completed_jobs.parse().unwrap());
+				}
+				Some(("total_jobs", total_jobs)) => {
+					self.header.total_jobs = Some(total_jobs.parse().unwrap());
+				}
+				Some(("cost_model", cost_model)) => {
+					match JobCostModel::from_header_value(cost_model) {
+						Some(cost_model) => {
+							self.header.cost_model = Some(cost_model);
+						}
+						None => {
+							report_error(format!("{}: invalid cost_model [{}]", self.file_err_msg, cost_model));
+							exit(EXIT_ERROR);
+						}
+					}
+				}
+				Some(("projection_strata", projection_strata)) => {
+					match CountProjection::completed_from_header_value(projection_strata) {
+						Some(completed) => {
+							self.header.projection_strata = Some(completed);
+						}
+						None => {
+							report_error(format!("{}: invalid projection_strata [{}]", self.file_err_msg, projection_strata));
+							exit(EXIT_ERROR);
+						}
+					}
+				}
+				Some(("projection_ratio", projection_ratio)) => {
+					match RatioEstimator::from_header_value(projection_ratio) {
+						Some(projection_ratio) => {
+							self.header.projection_ratio = Some(projection_ratio);
+						}
+						None => {
+							report_error(format!("{}: invalid projection_ratio [{}]", self.file_err_msg, projection_ratio));
+							exit(EXIT_ERROR);
+						}
+					}
+				}
+				Some(("config", config)) => {
+					match RunConfig::from_header_value(config) {
+						Some(config) => {
+							self.header.config = Some(config);
+						}
+						None => {
+							report_error(format!("{}: invalid config [{}]", self.file_err_msg, config));
+							exit(EXIT_ERROR);
+						}
+					}
+				}
+				// canonical encodings, like the jobs: "12345,67890,..."
+				Some(("frontier", frontier)) => {
+					for item in frontier.split(',').filter(|item| !item.is_empty()) {
+						match item.parse() {
+							Ok(polycube_enc) => {
+								self.header.frontier.push(polycube_enc);
+							}
+							Err(_) => {
+								report_error(format!("{}: invalid frontier polycube [{}]", self.file_err_msg, item));
+								exit(EXIT_ERROR);
+							}
+						}
+					}
+				}
+				// "1:exact,2:exact,...,9:partial,10:missing"
+				Some(("count_status", count_status)) => {
+					for item in count_status.split(',') {
+						let parsed = item.split_once(':').and_then(|(n, status)| Some((n.parse::<u8>().ok()?, CountStatus::from_name(status)?)));
+						match parsed {
+							Some((n, status)) if (n as usize) < MAX_N => {
+								self.header.count_status.insert(n, status);
+							}
+							_ => {
+								report_error(format!("{}: invalid count_status [{}]", self.file_err_msg, item));
+								exit(EXIT_ERROR);
+							}
+						}
+					}
+				}
+				// ignore fields written by newer versions
+				Some(_) => {}
+				None => {
+					self.pending = Some(self.parse_job_line());
+					return;
+				}
+			}
+		}
+	}
+
+	// look at the next job without consuming it
+	pub fn peek(&mut self) -> Option<u128> {
+		if self.pending.is_none() && self.read_line() {
+			self.pending = Some(self.parse_job_line());
+		}
+		self.pending
+	}
+
+	// hand back a job that was returned by next() but couldn't be used
+	pub fn push_back(&mut self, polycube_enc: u128) {
+		self.pending = Some(polycube_enc);
+		self.jobs_read -= 1;
+	}
+
+	// the number of jobs that have not yet been returned by next()
+	pub fn remaining(&self) -> usize {
+		self.job_count - self.jobs_read
+	}
+}
+
+impl Iterator for JobFileReader {
+	type Item = u128;
+
+	fn next(&mut self) -> Option<u128> {
+		let polycube_enc = match self.pending.take() {
+			Some(polycube_enc) => polycube_enc,
+			None => {
+				if !self.read_line() {
+					return None;
+				}
+				self.parse_job_line()
+			}
+		};
+		self.jobs_read += 1;
+		Some(polycube_enc)
+	}
+}
+
+// the most problems of each kind printed when checking a job file
+const MAX_REPORTED_PROBLEMS: usize = 10;
+
+// finds the jobs that appear more than once in a job file without
+//   keeping every job in memory: a first pass marks a bit for each
+//   job's hash, keeping the jobs whose bit was already marked (which
+//   include the second appearance of every duplicate, along with a few
+//   that only share a bit with another job), and a second pass, only
+//   needed if any were kept, counts how often each of those appears
+pub struct DuplicateFinder {
+	bits: Vec<u64>,
+	// the jobs kept in the first pass, with their counts in the second
+	suspects: BTreeMap<u128, usize>
+}
+
+impl DuplicateFinder {
+	// about 16 bits per job (so around 1 in 32 jobs is kept), up to
+	//   128MB for the largest files
+	pub fn new(expected_jobs: usize) -> DuplicateFinder {
+		let bit_count = expected_jobs.saturating_mul(16).next_power_of_two().clamp(1 << 16, 1 << 30);
+		DuplicateFinder {
+			bits: vec![0; bit_count / 64],
+			suspects: BTreeMap::new()
+		}
+	}
+
+	fn bit(&self, polycube_enc: u128) -> usize {
+		// the splitmix64 finalizer, over both halves of the encoding
+		let mut x = (polycube_enc as u64) ^ ((polycube_enc >> 64) as u64).wrapping_mul(0x9e3779b97f4a7c15);
+		x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+		x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+		(x ^ (x >> 31)) as usize & (self.bits.len() * 64 - 1)
+	}
+
+	// the first pass
+	pub fn add(&mut self, polycube_enc: u128) {
+		let bit = self.bit(polycube_enc);
+		if self.bits[bit / 64] & (1 << (bit % 64)) != 0 {
+			self.suspects.insert(polycube_enc, 0);
+		}
+		self.bits[bit / 64] |= 1 << (bit % 64);
+	}
+
+	pub fn needs_second_pass(&self) -> bool {
+		!self.suspects.is_empty()
+	}
+
+	// the second pass, over the same jobs, returning the jobs that
+	//   appear more than once
+	pub fn find_duplicates(mut self, jobs: impl Iterator<Item = u128>) -> Vec<u128> {
+		if self.suspects.is_empty() {
+			return Vec::new();
+		}
+		for polycube_enc in jobs {
+			if let Some(count) = self.suspects.get_mut(&polycube_enc) {
+				*count += 1;
+			}
+		}
+		self.suspects.into_iter().filter(|(_polycube_enc, count)| *count > 1).map(|(polycube_enc, _count)| polycube_enc).collect()
+	}
+}
+
+// the results of checking a resume file: the problems found, and the
+//   features of its jobs, counted along the way so a run resuming from
+//   it doesn't have to read the file again for its census
+pub struct ResumeFileCheck {
+	pub problems: Vec<String>,
+	pub census: BTreeMap<JobFeatures, CensusCount>
+}
+
+// rebuild every job in a resume file and check that each is a
+//   canonical encoding of a polycube of size spawn_n, that no job
+//   appears twice, and that the counts and job bookkeeping in the
+//   header add up, printing a summary and returning the problems
+// the file is read once, or twice if it may have duplicate jobs
+//   (see DuplicateFinder)
+pub fn check_resume_file(resume_file_path: &PathBuf) -> ResumeFileCheck {
+	if !json_events() {
+		println!("checking resume file [{}]...", resume_file_path.to_string_lossy());
+	}
+	let mut reader = JobFileReader::open(resume_file_path, JobFileFormat::Resume);
+	let header_n = reader.header.n;
+	let spawn_n = reader.header.spawn_n;
+	let mut problems: Vec<String> = Vec::new();
+	let report = |problems: &mut Vec<String>, problem: String| {
+		if json_events() {
+			emit_event("warning", serde_json::json!({ "message": problem, "resume_file": resume_file_path.to_string_lossy() }));
+		} else {
+			println!("    {}", problem);
+		}
+		problems.push(problem);
+	};
+	if header_n as usize >= MAX_N {
+		report(&mut problems, format!("n={} is larger than the limit of {}", header_n, MAX_N - 1));
+	}
+	if spawn_n > 0 && spawn_n >= header_n {
+		report(&mut problems, format!("spawn_n={} is not smaller than n={}", spawn_n, header_n));
+	}
+
+	// the jobs are the polycubes of size spawn_n (or, for a run that
+	//   began from a polycubes file, of the size of the first job)
+	let job_n: Option<u8> = if spawn_n > 0 {
+		Some(spawn_n)
+	} else {
+		reader.peek().map(|polycube_enc| {
+			let mut p = Polycube::new(false);
+			p.rebuild_from_encoding(polycube_enc);
+			p.n
+		})
+	};
+	let mut wrong_size: usize = 0;
+	let mut not_canonical: usize = 0;
+	let mut job_count: usize = 0;
+	let mut duplicate_finder = DuplicateFinder::new(reader.job_count);
+	let mut census: BTreeMap<JobFeatures, CensusCount> = BTreeMap::new();
+	for polycube_enc in reader.by_ref() {
+		let mut p = Polycube::new(false);
+		p.rebuild_from_encoding(polycube_enc);
+		if Some(p.n) != job_n {
+			wrong_size += 1;
+			if wrong_size <= MAX_REPORTED_PROBLEMS {
+				report(&mut problems, format!("job [{}] has {} cubes, not {}", encoding_to_str(polycube_enc), p.n, job_n.unwrap_or(0)));
+			}
+		}
+		let canonical_enc = p.find_canonical_info(None).enc;
+		if canonical_enc != polycube_enc {
+			not_canonical += 1;
+			if not_canonical <= MAX_REPORTED_PROBLEMS {
+				report(&mut problems, format!("job [{}] is not canonical: its polycube encodes to [{}]", encoding_to_str(polycube_enc), encoding_to_str(canonical_enc)));
+			}
+		}
+		census.entry(JobFeatures::of_polycube(&p)).or_default().jobs += 1.0;
+		duplicate_finder.add(polycube_enc);
+		job_count += 1;
+	}
+	let duplicate_jobs = if duplicate_finder.needs_second_pass() {
+		duplicate_finder.find_duplicates(JobFileReader::open(resume_file_path, JobFileFormat::Resume))
+	} else {
+		Vec::new()
+	};
+	let duplicates = duplicate_jobs.len();
+	for polycube_enc in duplicate_jobs.iter().take(MAX_REPORTED_PROBLEMS) {
+		report(&mut problems, format!("job [{}] appears more than once", encoding_to_str(*polycube_enc)));
+	}
+	for (kind, count) in [("jobs of the wrong size", wrong_size), ("jobs that are not canonical", not_canonical), ("duplicate jobs", duplicates)] {
+		if count > MAX_REPORTED_PROBLEMS {
+			report(&mut problems, format!("... and {} more {}", count - MAX_REPORTED_PROBLEMS, kind));
+		}
+	}
+
+	// the frontier's polycubes are counted, but the jobs under them
+	//   are still to be found
+	let mut frontier_n: Option<u8> = None;
+	let mut frontier_problems: usize = 0;
+	for polycube_enc in reader.header.frontier.iter() {
+		let mut p = Polycube::new(false);
+		p.rebuild_from_encoding(*polycube_enc);
+		frontier_n = Some(frontier_n.map_or(p.n, |frontier_n| frontier_n.min(p.n)));
+		let problem = if p.n >= spawn_n {
+			Some(format!("frontier polycube [{}] has {} cubes, but should be smaller than spawn_n={}", encoding_to_str(*polycube_enc), p.n, spawn_n))
+		} else if p.find_canonical_info(None).enc != *polycube_enc {
+			Some(format!("frontier polycube [{}] is not canonical", encoding_to_str(*polycube_enc)))
+		} else {
+			None
+		};
+		if let Some(problem) = problem {
+			frontier_problems += 1;
+			if frontier_problems <= MAX_REPORTED_PROBLEMS {
+				report(&mut problems, problem);
+			}
+		}
+	}
+	if frontier_problems > MAX_REPORTED_PROBLEMS {
+		report(&mut problems, format!("... and {} more problems with the frontier", frontier_problems - MAX_REPORTED_PROBLEMS));
+	}
+
+	// the initial delegator counts every polycube up to spawn_n,
+	//   or up to the size of the smallest polycube in its frontier
+	if spawn_n > 0 {
+		for i in 1..frontier_n.unwrap_or(spawn_n).min(spawn_n).min(MAX_N as u8 - 1) + 1 {
+			let count = reader.header.n_counts.get(&i).copied().unwrap_or(0);
+			if count != WELL_KNOWN_N_COUNTS[i as usize] {
+				report(&mut problems, format!("the count for n={} is {}, but should be {}", i, count, WELL_KNOWN_N_COUNTS[i as usize]));
+			}
+		}
+	}
+	// counts beyond the jobs' size (or the frontier's) can't be exact
+	//   while jobs remain
+	for (i, status) in reader.header.count_status.iter() {
+		if *status == CountStatus::Exact && (job_n.is_some_and(|job_n| *i > job_n) || frontier_n.is_some_and(|frontier_n| *i > frontier_n)) {
+			report(&mut problems, format!("the count for n={} is marked exact, but there are jobs left to count it", i));
+		}
+	}
+	match (reader.header.completed_jobs, reader.header.total_jobs) {
+		(Some(completed_jobs), Some(total_jobs)) => {
+			if !json_events() {
+				println!("    {} completed + {} remaining jobs, of {}", completed_jobs, job_count, total_jobs);
+			}
+			if !json_events() && frontier_n.is_some() {
+				println!("    and the jobs under the {} polycubes of the frontier", reader.header.frontier.len());
+			}
+			if frontier_n.is_some() && completed_jobs + job_count > total_jobs {
+				report(&mut problems, format!("{} completed + {} remaining jobs should be at most {}, but are {}",
+					completed_jobs, job_count, total_jobs, completed_jobs + job_count));
+			}
+			if frontier_n.is_none() && completed_jobs + job_count != total_jobs {
+				report(&mut problems, format!("{} completed + {} remaining jobs should be {}, but are {}",
+					completed_jobs, job_count, total_jobs, completed_jobs + job_count));
+			}
+			if spawn_n > 0 && (spawn_n as usize) < MAX_N && total_jobs != WELL_KNOWN_N_COUNTS[spawn_n as usize] {
+				report(&mut problems, format!("the run started with {} jobs, but there are {} polycubes of size spawn_n={}",
+					total_jobs, WELL_KNOWN_N_COUNTS[spawn_n as usize], spawn_n));
+			}
+		}
+		_ if json_events() => {}
+		_ => {
+			println!("    {} remaining jobs (this resume file doesn't record how many were completed)", job_count);
+		}
+	}
+	if problems.is_empty() && !json_events() {
+		println!("    all {} jobs are canonical, unique polycubes of size {}", job_count, job_n.map_or("-".to_string(), |n| n.to_string()));
+	}
+	ResumeFileCheck { problems, census }
+}
+
+// a polycubes file contains a list of polycube encodings
+//   where each line is a polycube encoding, except for
+//   the first line, which is n
+// polycubes are written in batches, each closed by a trailer with
+//   the number of polycubes in the batch: a new file is written to a
+//   temporary file and renamed into place, and later batches are
+//   appended and fsynced, so a crash can only ever leave a partial
+//   batch at the end, which is dropped before the next append
+pub fn write_polycubes_file(
+		n: u8,
+		polycubes_file_path: &str,
+		polycubes_to_write_to_disk: &Vec<u128>) {
+	let polycubes_file_path: PathBuf = PathBuf::from(polycubes_file_path);
+	if !json_events() {
+		println!("\nwriting {} polycubes to [{}]...", polycubes_to_write_to_disk.len(), polycubes_file_path.to_str().unwrap());
+	}
+	let write_batch = |buf: &mut BufWriter<File>| -> std::io::Result<()> {
+		for polycube_enc in polycubes_to_write_to_disk {
+			let base94 = encoding_to_str(*polycube_enc);
+			//let back_to_int = str_to_encoding(&base94);
+			//if back_to_int != *polycube_enc {
+			//	println!("ERROR! for pcube encoding [{}], we have base94 [{}] which converts back to [{}]", polycube_enc, base94, back_to_int);
+			//	exit(1);
+			//}
+			//let mut p = Polycube::new(false);
+			//p.rebuild_from_encoding(*polycube_enc);
+			//let recreated_enc = p.find_canonical_info(None).enc;
+			//if  recreated_enc != *polycube_enc {
+			//	println!("ERROR! for pcube encoding [{}], we have recreated a polycube of size [{}] with encoding [{}]", polycube_enc, p.n, recreated_enc);
+			//} else {
+			//	println!("for pcube encoding [{}], we have recreated a polycube of size [{}] with encoding [{}]", polycube_enc, p.n, recreated_enc);
+			//}
+			buf.write_all(format!("{}\n", base94).as_bytes())?;
+		}
+		buf.write_all(format!("{} {}\n", END_MARKER, polycubes_to_write_to_disk.len()).as_bytes())
+	};
+	// if creating a new file, write n as the first line
+	if !polycubes_file_path.exists() {
+		write_file_atomically(&polycubes_file_path, "polycubes file", |buf| {
+			buf.write_all(format!("{}\n", n).as_bytes())?;
+			write_batch(buf)
+		});
+		emit_polycubes_written_event(&polycubes_file_path, polycubes_to_write_to_disk.len());
+		return;
+	}
+	// otherwise append to the file, which has to end with a complete
+	//   batch (see prepare_polycubes_file), since nothing in it is
+	//   ever dropped here
+	let complete_len = match polycubes_file_state(&polycubes_file_path, n) {
+		Ok(PolycubesFileState::Complete { len }) => len,
+		Ok(_) => {
+			report_error(format!("polycubes file [{}] doesn't end with a complete batch, so it can't be appended to", polycubes_file_path.to_string_lossy()));
+			exit(EXIT_ERROR);
+		}
+		Err(err) => {
+			report_error(err);
+			exit(EXIT_ERROR);
+		}
+	};
+	let result = File::options().write(true).open(&polycubes_file_path).and_then(|mut file_buf| {
+		file_buf.seek(SeekFrom::Start(complete_len))?;
+		let mut buf = BufWriter::new(file_buf);
+		write_batch(&mut buf)?;
+		let file_buf = buf.into_inner().map_err(|err| err.into_error())?;
+		file_buf.sync_all()
+	});
+	if let Err(err) = result {
+		report_error(format!("writing to polycubes file [{}]: {}", polycubes_file_path.to_string_lossy(), err));
+		// if we have an error writing polycubes file, there's no
+		//   point in continuing
+		exit(EXIT_ERROR);
+	}
+	emit_polycubes_written_event(&polycubes_file_path, polycubes_to_write_to_disk.len());
+}
+
+pub fn emit_polycubes_written_event(polycubes_file_path: &Path, polycubes: usize) {
+	if json_events() {
+		emit_event("polycubes_written", serde_json::json!({
+			"file": polycubes_file_path.to_string_lossy(),
+			"polycubes": polycubes
+		}));
+	}
+}
+
+// how a polycubes file ends, which decides whether it can be appended to
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PolycubesFileState {
+	// the file ends with a complete batch (or just the n line)
+	Complete { len: u64 },
+	// the file has batches, but ends with some of a batch that was
+	//   being written when the run was interrupted
+	Interrupted { complete_len: u64, len: u64 },
+	// the file has polycubes but no trailers at all, so it was written
+	//   before batches were closed with trailers
+	Legacy { len: u64 }
+}
+
+// find how the (uncompressed) polycubes file ends, and where its last
+//   complete batch does
+pub fn polycubes_file_state(polycubes_file_path: &Path, n: u8) -> Result<PolycubesFileState, String> {
+	let file_err_msg: String = format!("reading polycubes file [{}]", polycubes_file_path.to_string_lossy());
+	let file = File::open(polycubes_file_path).map_err(|err| format!("{}: {}", file_err_msg, err))?;
+	polycubes_state_from_reader(BufReader::new(file), n).map_err(|err| format!("{}: {}", file_err_msg, err))
+}
+
+pub fn polycubes_state_from_reader(mut buf: impl BufRead, n: u8) -> Result<PolycubesFileState, String> {
+	let mut line = String::new();
+	let mut offset: u64 = buf.read_line(&mut line).map_err(|err| err.to_string())? as u64;
+	if !line.ends_with('\n') || line.trim_end().parse::<u8>() != Ok(n) {
+		return Err(format!("can only append polycubes with n={} to a polycubes file that begins with n={}", n, n));
+	}
+	let mut complete_len = offset;
+	let mut trailer_seen = false;
+	let mut batch_len: usize = 0;
+	loop {
+		line.clear();
+		let len = buf.read_line(&mut line).map_err(|err| err.to_string())?;
+		offset += len as u64;
+		// a line without a newline was cut off
+		if len == 0 || !line.ends_with('\n') {
+			break;
+		}
+		match line.trim_end().strip_prefix(END_MARKER) {
+			Some(count) if count.trim().parse::<usize>() == Ok(batch_len) => {
+				complete_len = offset;
+				trailer_seen = true;
+				batch_len = 0;
+			}
+			// a trailer that doesn't match its batch ends the complete part
+			Some(_) => {
+				trailer_seen = true;
+				break;
+			}
+			None => {
+				batch_len += 1;
+			}
+		}
+	}
+	// read to the end, for the file's length
+	offset += std::io::copy(&mut buf, &mut std::io::sink()).map_err(|err| err.to_string())?;
+	Ok(if complete_len == offset {
+		PolycubesFileState::Complete { len: offset }
+	} else if trailer_seen {
+		PolycubesFileState::Interrupted { complete_len, len: offset }
+	} else {
+		PolycubesFileState::Legacy { len: offset }
+	})
+}
+
+// check, before a run starts, that the polycubes file (if it exists)
+//   can be appended to
+// the end of a batch that was interrupted is only dropped when asked
+//   to with truncate, and a file without any trailers is never
+//   appended to, since there's no telling where it was cut short
+pub fn prepare_polycubes_file(polycubes_file_path: &str, n: u8, truncate: bool) -> Result<(), String> {
+	let path = Path::new(polycubes_file_path);
+	if !path.exists() {
+		return Ok(());
+	}
+	match polycubes_file_state(path, n)? {
+		PolycubesFileState::Complete { .. } => Ok(()),
+		PolycubesFileState::Legacy { .. } => Err(format!("polycubes file [{}] has no \"{}\" trailers, so it was written by an older version, \
+			and can't be appended to: to add trailers, gzip it and then run: convert <file>.txt.gz --legacy --to polycubes --output <new-file>.txt",
+			polycubes_file_path, END_MARKER)),
+		PolycubesFileState::Interrupted { complete_len, len } if truncate => {
+			println!("dropping {} bytes of an interrupted write after the last complete batch in polycubes file [{}]",
+				len - complete_len, polycubes_file_path);
+			File::options().write(true).open(path).and_then(|file| {
+				file.set_len(complete_len)?;
+				file.sync_all()
+			}).map_err(|err| format!("truncating polycubes file [{}]: {}", polycubes_file_path, err))
+		}
+		PolycubesFileState::Interrupted { complete_len, len } => Err(format!("polycubes file [{}] ends with {} bytes of an interrupted write \
+			after its last complete batch: to drop them and append after that batch, run again with --truncate-polycubes-file",
+			polycubes_file_path, len - complete_len))
+	}
+}
+
+// a lock file held for the length of a run, so that two runs can't
+//   share a halt file or append to the same polycubes file
+// the run holds an exclusive lock on the file itself, which the os
+//   releases when the run exits, however it exits, so there's no
+//   stale lock to detect or take over: the file is left in place,
+//   since removing it would let a run lock the old file while
+//   another creates and locks a new one
+// while it's held, the file names the pid and start time of the run
+//   holding it, for the error given to any other run
+pub struct RunLock {
+	file: File
+}
+
+impl RunLock {
+	// returns the reason if the lock is held by another run
+	pub fn acquire(path: PathBuf, description: &str) -> Result<RunLock, String> {
+		let mut file = match File::options().read(true).write(true).create(true).truncate(false).open(&path) {
+			Ok(file) => file,
+			Err(err) => {
+				report_error(format!("opening lock file [{}]: {}", path.to_string_lossy(), err));
+				exit(EXIT_ERROR);
+			}
+		};
+		match file.try_lock() {
+			Ok(()) => {}
+			Err(std::fs::TryLockError::WouldBlock) => {
+				let (pid, started) = RunLock::read_holder(&mut file);
+				return Err(match pid {
+					Some(pid) => format!("{} is in use by another run (pid {}, started {})",
+						description, pid, started.as_deref().unwrap_or("at an unknown time")),
+					// the holder may not have written its pid yet
+					None => format!("{} is in use by another run, which is just starting", description)
+				});
+			}
+			Err(std::fs::TryLockError::Error(err)) => {
+				report_error(format!("locking lock file [{}]: {}", path.to_string_lossy(), err));
+				exit(EXIT_ERROR);
+			}
+		}
+		let contents = format!("pid={}\nstarted={}\n", std::process::id(), Local::now().to_rfc3339());
+		if let Err(err) = file.set_len(0).and_then(|_| file.write_all(contents.as_bytes())).and_then(|_| file.sync_all()) {
+			report_error(format!("writing lock file [{}]: {}", path.to_string_lossy(), err));
+			exit(EXIT_ERROR);
+		}
+		Ok(RunLock { file })
+	}
+
+	// the pid and start time written in a lock file, if any
+	fn read_holder(file: &mut File) -> (Option<u32>, Option<String>) {
+		let mut contents = String::new();
+		let _ = file.read_to_string(&mut contents);
+		let mut pid: Option<u32> = None;
+		let mut started: Option<String> = None;
+		for line in contents.lines() {
+			match line.split_once('=') {
+				Some(("pid", value)) => {
+					pid = value.parse().ok();
+				}
+				Some(("started", value)) => {
+					started = Some(value.to_string());
+				}
+				_ => {}
+			}
+		}
+		(pid, started)
+	}
+}
+
+impl Drop for RunLock {
+	// the holder's details are cleared before the os lock is released
+	//   (when the file is closed), so a free lock file is empty
+	fn drop(&mut self) {
+		let _ = self.file.set_len(0);
+	}
+}
+
+// the path of a file in a run's work directory, which holds its halt
+//   file, lock file, and any resume and quarantine files it writes,
+//   and by default is the executable's directory
+pub fn create_work_file(work_dir: Option<&Path>, filename: &str) -> PathBuf {
+	match work_dir {
+		Some(work_dir) => work_dir.join(filename),
+		None => create_executable_sibling_file(filename)
+	}
+}
+
+pub fn create_executable_sibling_file(filename: &str) -> PathBuf {
+	return match env::current_exe() {
+		Ok(executable_path) => {
+			executable_path.parent().unwrap().join(filename)
+		}
+		Err(_) => {
+			println!("error: could not determine current executable path");
+			exit(1);
+		}
+	};
+}
+
+pub fn seconds_to_dur(s: f64) -> String {
+	let days = (s / 86400.0).floor();
+	let hours = ((s - (days * 86400.0)) / 3600.0).floor();
+	let minutes = ((s - (days * 86400.0) - (hours * 3600.0)) / 60.0).floor();
+	let seconds = s - (days * 86400.0) - (hours * 3600.0) - (minutes * 60.0);
+	let fsec = format!("{}{:.3}", if seconds < 10.0 { "0" } else { "" }, seconds);
+	if days > 0.0 {
+		return format!("{} days {:0>2}h:{:0>2}m:{}s", days, hours, minutes, fsec);
+	}
+	return format!("{:0>2}h:{:0>2}m:{}s", hours, minutes, fsec);
+}
+
+pub fn validate_polycubes_file_arg(file_arg: &str, arg_name: &str) -> Result<PathBuf, String> {
+	let file_path = PathBuf::from(file_arg);
+	match file_path.try_exists() {
+		Ok(_) => {}
+		Err(_) => {
+			return Err(format!("{} [{}] does not have permissions necessary for access", arg_name, file_arg));
+		}
+	}
+	if file_path.is_dir() {
+		return Err(format!("{} [{}] is not a regular file or does not have permissions \
+			necessary for access", arg_name, file_arg));
+	}
+	if !file_path.extension().unwrap().eq("txt") {
+		return Err(format!("{} [{}] must be a .txt file", arg_name, file_arg));
+	}
+	Ok(file_path)
+}
+
+// the work directory is kept as an absolute path, so that a resume
+//   file taken from it works from any directory
+pub fn validate_work_dir_arg(dir_arg: &str) -> Result<PathBuf, String> {
+	let dir_path = PathBuf::from(dir_arg);
+	if !dir_path.is_dir() {
+		return Err(format!("<work-dir> [{}] is not a directory or does not have permissions necessary for access", dir_arg));
+	}
+	std::path::absolute(&dir_path).map_err(|err| format!("<work-dir> [{}]: {}", dir_arg, err))
+}
+
+pub fn validate_resume_file_arg(file_arg: &str, arg_name: &str) -> Result<PathBuf, String> {
+	let file_path = PathBuf::from(file_arg);
+	if !file_path.is_file() {
+		return Err(format!("{} [{}] is not a regular file, does not exist, or does not have permissions \
+			necessary for access", arg_name, file_arg));
+	}
+	if !file_path.extension().unwrap().eq("gz") {
+		return Err(format!("{} [{}] must be a .txt.gz file", arg_name, file_arg));
+	}
+	Ok(file_path)
+}
+
+// running sums for estimating the ratio y/x over a population of
+//   jobs from the jobs seen so far, where x is known for every job
+//   and y only for the jobs that have completed
+#[derive(Clone, Copy, Default)]
+pub struct RatioEstimator {
+	pub samples: f64,
+	pub sum_x: f64,
+	pub sum_y: f64,
+	pub sum_xx: f64,
+	pub sum_xy: f64,
+	pub sum_yy: f64
+}
+
+impl RatioEstimator {
+	pub fn add(&mut self, x: f64, y: f64) {
+		self.samples += 1.0;
+		self.sum_x += x;
+		self.sum_y += y;
+		self.sum_xx += x * x;
+		self.sum_xy += x * y;
+		self.sum_yy += y * y;
+	}
+
+	pub fn ratio(&self) -> Option<f64> {
+		if self.sum_x > 0.0 { Some(self.sum_y / self.sum_x) } else { None }
+	}
+
+	// the standard error of the ratio, from the spread of each
+	//   sample around the line y = ratio * x, where population is the
+	//   total number of jobs (the error shrinks to 0 as the samples
+	//   approach the whole population)
+	pub fn ratio_std_err(&self, population: f64) -> Option<f64> {
+		let ratio = self.ratio()?;
+		if self.samples < 2.0 {
+			return None;
+		}
+		let residual_sq = (self.sum_yy - 2.0 * ratio * self.sum_xy + ratio * ratio * self.sum_xx).max(0.0);
+		let residual_var = residual_sq / (self.samples - 1.0);
+		let mean_x = self.sum_x / self.samples;
+		let sampled_fraction = (self.samples / population).min(1.0);
+		Some((residual_var * (1.0 - sampled_fraction) / self.samples).sqrt() / mean_x)
+	}
+
+	// "samples:...,sum_x:...", for writing to a resume file
+	pub fn to_header_value(&self) -> String {
+		format!("samples:{},sum_x:{},sum_y:{},sum_xx:{},sum_xy:{},sum_yy:{}",
+			self.samples, self.sum_x, self.sum_y, self.sum_xx, self.sum_xy, self.sum_yy)
+	}
+
+	pub fn from_header_value(value: &str) -> Option<RatioEstimator> {
+		let mut estimator = RatioEstimator::default();
+		for item in value.split(',') {
+			let (key, value) = item.split_once(':')?;
+			let value: f64 = value.parse().ok()?;
+			match key {
+				"samples" => estimator.samples = value,
+				"sum_x" => estimator.sum_x = value,
+				"sum_y" => estimator.sum_y = value,
+				"sum_xx" => estimator.sum_xx = value,
+				"sum_xy" => estimator.sum_xy = value,
+				"sum_yy" => estimator.sum_yy = value,
+				_ => return None
+			}
+		}
+		Some(estimator)
+	}
+}
+
+// jobs vary in cost by orders of magnitude, so a job's cost is
+//   estimated from a cheap probe of its subtree: the number of its
+//   descendants at probe_n (spawn_n+2, or spawn_n+1 if that's n), with
+//   seconds per descendant calibrated from the completed jobs
+// the probe costs nothing to compute: a completed job's probe is its
+//   own count at probe_n, and since every polycube of size probe_n
+//   descends from exactly one job, the probe total over all jobs is
+//   the well-known count for probe_n, so the remaining jobs' probe
+//   total is what hasn't been counted yet
+// when there's no well-known count (for a run that began from a
+//   polycubes file), every job is given the same weight
+#[derive(Clone, Copy)]
+pub struct JobCostModel {
+	// 0 when every job has the same weight
+	pub probe_n: u8,
+	// x is a job's probe, and y its cost in seconds
+	pub cost: RatioEstimator
+}
+
+// how many jobs are worked on at once for the rest of a run: the
+//   expected number, and the range it's expected to be in
+pub struct Parallelism {
+	pub expected: f64,
+	pub low: f64,
+	pub high: f64
+}
+
+impl Parallelism {
+	// every worker thread is expected to be busy until fewer jobs than
+	//   that remain, but the band also allows for them to be no busier
+	//   than they have been on average so far, sampled from the
+	//   workers that aren't waiting for a job (the seconds of the
+	//   completed jobs per second would leave out the jobs still being
+	//   worked on)
+	pub fn new(threads: usize, remaining_jobs: usize, busy_worker_sec: f64, sampled_sec: f64) -> Parallelism {
+		let workers = threads.min(remaining_jobs).max(1) as f64;
+		let average = if sampled_sec > 0.0 && busy_worker_sec > 0.0 { busy_worker_sec / sampled_sec } else { workers };
+		Parallelism { expected: workers, low: average.min(workers), high: workers }
+	}
+}
+
+// the completed fraction of the work, and the estimated seconds
+//   remaining with a 95% confidence band
+pub struct ProgressEstimate {
+	pub fraction_complete: f64,
+	pub remaining_sec: f64,
+	pub remaining_sec_low: f64,
+	pub remaining_sec_high: f64
+}
+
+impl JobCostModel {
+	pub fn new(n: u8, spawn_n: u8, weigh_by_probe: bool) -> JobCostModel {
+		let probe_n = (spawn_n + 2).min(n);
+		// the counts past n=20 are only estimates
+		let probe_n = if weigh_by_probe && spawn_n > 0 && probe_n > spawn_n && probe_n <= 20 { probe_n } else { 0 };
+		JobCostModel { probe_n, cost: RatioEstimator::default() }
+	}
+
+	// a completed job's probe, from its counts
+	pub fn probe(&self, results: &[usize; MAX_N]) -> f64 {
+		if self.probe_n == 0 { 1.0 } else { results[self.probe_n as usize] as f64 }
+	}
+
+	// the probe total over every job, and over the completed jobs
+	pub fn probe_totals(&self, total_jobs: usize, completed_jobs: isize) -> (f64, f64) {
+		if self.probe_n == 0 {
+			(total_jobs as f64, completed_jobs.max(0) as f64)
+		} else {
+			(WELL_KNOWN_N_COUNTS[self.probe_n as usize] as f64, unsafe { N_COUNTS[self.probe_n as usize] } as f64)
+		}
+	}
+
+	// the probe total of the jobs yet to be completed, if the jobs are
+	//   weighed by their probes, for projecting the final count
+	pub fn remaining_probe(&self, total_jobs: usize, completed_jobs: isize) -> Option<f64> {
+		if self.probe_n == 0 {
+			return None;
+		}
+		let (total_probe, completed_probe) = self.probe_totals(total_jobs, completed_jobs);
+		Some((total_probe - completed_probe).max(0.0))
+	}
+
+	// parallelism turns the remaining job-seconds into wall-clock
+	//   seconds, and its range widens the band along with the
+	//   uncertainty in the cost of the remaining jobs
+	pub fn estimate(&self, total_jobs: usize, completed_jobs: isize, parallelism: &Parallelism) -> Option<ProgressEstimate> {
+		let (total_probe, completed_probe) = self.probe_totals(total_jobs, completed_jobs);
+		let ratio = self.cost.ratio()?;
+		if total_probe <= 0.0 || parallelism.low <= 0.0 {
+			return None;
+		}
+		let remaining_probe = (total_probe - completed_probe).max(0.0);
+		let std_err = self.cost.ratio_std_err(total_jobs as f64).unwrap_or(ratio);
+		// the last jobs run with some of the threads idle, which can
+		//   add up to about one job's time
+		let remaining_jobs = (total_jobs as isize - completed_jobs).max(1) as f64;
+		let tail_sec = (ratio + 1.96 * std_err) * remaining_probe / remaining_jobs;
+		Some(ProgressEstimate {
+			fraction_complete: completed_probe / total_probe,
+			remaining_sec: ratio * remaining_probe / parallelism.expected,
+			remaining_sec_low: (ratio - 1.96 * std_err).max(0.0) * remaining_probe / parallelism.high,
+			remaining_sec_high: (ratio + 1.96 * std_err) * remaining_probe / parallelism.low + tail_sec
+		})
+	}
+
+	// "probe_n:9,samples:...", for writing to a resume file
+	pub fn to_header_value(&self) -> String {
+		format!("probe_n:{},{}", self.probe_n, self.cost.to_header_value())
+	}
+
+	pub fn from_header_value(value: &str) -> Option<JobCostModel> {
+		let (probe_n, cost) = value.split_once(',')?;
+		Some(JobCostModel {
+			probe_n: probe_n.strip_prefix("probe_n:")?.parse().ok()?,
+			cost: RatioEstimator::from_header_value(cost)?
+		})
+	}
+}
+
+// features of a job that its number of descendants is expected to
+//   depend on, used to group similar jobs when projecting the final count
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct JobFeatures {
+	pub symmetry_order: u8,
+	pub bounding_box: [u8; 3]
+}
+
+impl JobFeatures {
+	pub fn of_encoding(polycube_enc: u128) -> JobFeatures {
+		let mut polycube = Polycube::new(false);
+		polycube.rebuild_from_encoding(polycube_enc);
+		JobFeatures::of_polycube(&polycube)
+	}
+
+	pub fn of_polycube(polycube: &Polycube) -> JobFeatures {
+		JobFeatures {
+			symmetry_order: polycube.symmetry_order(),
+			bounding_box: polycube.bounding_box()
+		}
+	}
+
+	// "4/3x2x1", for writing to a resume file
+	pub fn to_header_key(&self) -> String {
+		format!("{}/{}x{}x{}", self.symmetry_order, self.bounding_box[0], self.bounding_box[1], self.bounding_box[2])
+	}
+
+	pub fn from_header_key(key: &str) -> Option<JobFeatures> {
+		let (symmetry_order, bounding_box) = key.split_once('/')?;
+		let dimensions: Vec<u8> = bounding_box.split('x').map(|d| d.parse().ok()).collect::<Option<Vec<u8>>>()?;
+		Some(JobFeatures {
+			symmetry_order: symmetry_order.parse().ok()?,
+			bounding_box: dimensions.try_into().ok()?
+		})
+	}
+}
+
+// sums of the completed jobs' counts at n, for one group of jobs
+#[derive(Clone, Copy, Default)]
+pub struct StratumSums {
+	pub samples: f64,
+	pub sum_y: f64,
+	pub sum_yy: f64
+}
+
+impl StratumSums {
+	pub fn add(&mut self, y: f64) {
+		self.samples += 1.0;
+		self.sum_y += y;
+		self.sum_yy += y * y;
+	}
+
+	pub fn merge(&mut self, other: &StratumSums) {
+		self.samples += other.samples;
+		self.sum_y += other.sum_y;
+		self.sum_yy += other.sum_yy;
+	}
+
+	pub fn mean(&self) -> f64 {
+		self.sum_y / self.samples
+	}
+
+	pub fn variance(&self) -> f64 {
+		if self.samples < 2.0 {
+			return 0.0;
+		}
+		((self.sum_yy - self.sum_y * self.sum_y / self.samples) / (self.samples - 1.0)).max(0.0)
+	}
+}
+
+// the number of jobs in one group of jobs, which is estimated (with
+//   the variance of the estimate) for jobs that are sampled rather
+//   than counted (see take_job_census)
+#[derive(Clone, Copy, Default)]
+pub struct CensusCount {
+	pub jobs: f64,
+	pub variance: f64
+}
+
+impl CensusCount {
+	pub fn merge(&mut self, other: &CensusCount) {
+		self.jobs += other.jobs;
+		self.variance += other.variance;
+	}
+}
+
+// a projection of the final count, with a 95% confidence interval
+#[derive(Clone, Copy)]
+pub struct ProjectedCount {
+	pub value: f64,
+	pub low: f64,
+	pub high: f64
+}
+
+impl ProjectedCount {
+	pub fn describe(&self) -> String {
+		format!("{:.0} (95%: {:.0} to {:.0})", self.value, self.low, self.high)
+	}
+}
+
+// projects the final count at n from the completed jobs' counts
+// when the jobs are weighed by their probes (see JobCostModel), the
+//   remaining jobs' probe total is known exactly, and the count is
+//   projected with a ratio estimator: the remaining jobs are expected
+//   to have as many polycubes of size n per polycube of size probe_n
+//   as the completed jobs, which holds up even though the cheapest
+//   jobs tend to complete first
+// otherwise it's projected with a stratified estimator: the jobs are
+//   grouped by their features, and each group's remaining jobs are
+//   expected to have the mean count of that group's completed jobs
+//   (or of all the completed jobs, for a group with fewer than 2
+//   completed)
+// the number of remaining jobs in each group comes from a census of
+//   the jobs that weren't completed when the run started, which is
+//   taken in the background (see take_job_census), and where it's
+//   estimated, its variance is added to the projection's
+#[derive(Default)]
+pub struct CountProjection {
+	// completed jobs' counts at n, including those of earlier runs
+	pub completed: BTreeMap<JobFeatures, StratumSums>,
+	// jobs not yet completed when this run started, by their features
+	pub census: Option<BTreeMap<JobFeatures, CensusCount>>,
+	pub completed_this_run: BTreeMap<JobFeatures, usize>,
+	// x is a completed job's probe, and y its count at n, including
+	//   those of earlier runs
+	pub by_probe: RatioEstimator
+}
+
+impl CountProjection {
+	pub fn new() -> CountProjection {
+		CountProjection::default()
+	}
+
+	pub fn add(&mut self, features: JobFeatures, count: usize, probe: f64) {
+		self.completed.entry(features).or_default().add(count as f64);
+		*self.completed_this_run.entry(features).or_default() += 1;
+		self.by_probe.add(probe, count as f64);
+	}
+
+	// counted is the count at n so far, and remaining_probe the probe
+	//   total of the jobs yet to be completed, if they're weighed by
+	//   their probes, out of total_jobs
+	pub fn project(&self, counted: usize, remaining_probe: Option<f64>, total_jobs: usize) -> Option<ProjectedCount> {
+		if let Some(remaining_probe) = remaining_probe {
+			return self.project_by_probe(counted, remaining_probe, total_jobs);
+		}
+		let census = self.census.as_ref()?;
+		let mut pooled = StratumSums::default();
+		for sums in self.completed.values() {
+			pooled.merge(sums);
+		}
+		if pooled.samples < 2.0 {
+			return None;
+		}
+		let mut remaining_count: f64 = 0.0;
+		let mut variance: f64 = 0.0;
+		for (features, census_count) in census.iter() {
+			let remaining = (census_count.jobs - self.completed_this_run.get(features).copied().unwrap_or(0) as f64).max(0.0);
+			if remaining == 0.0 {
+				continue;
+			}
+			let sums = match self.completed.get(features) {
+				Some(sums) if sums.samples >= 2.0 => sums,
+				_ => &pooled
+			};
+			// with the finite population correction, since the completed
+			//   and remaining jobs together make up the whole group
+			let correction = remaining / (remaining + sums.samples);
+			remaining_count += remaining * sums.mean();
+			variance += remaining * remaining * sums.variance() / sums.samples * correction
+				+ sums.mean() * sums.mean() * census_count.variance;
+		}
+		let value = counted as f64 + remaining_count;
+		let margin = 1.96 * variance.sqrt();
+		Some(ProjectedCount {
+			value,
+			low: (value - margin).max(counted as f64),
+			high: value + margin
+		})
+	}
+
+	// the variance is that of predicting the remaining jobs' counts,
+	//   which is both the variance of the ratio and the spread of the
+	//   remaining jobs around it (with no finite population correction,
+	//   since the remaining jobs aren't a random sample of them all)
+	fn project_by_probe(&self, counted: usize, remaining_probe: f64, total_jobs: usize) -> Option<ProjectedCount> {
+		let ratio = self.by_probe.ratio()?;
+		let sums = &self.by_probe;
+		if sums.samples < 2.0 {
+			return None;
+		}
+		let residual_var = (sums.sum_yy - 2.0 * ratio * sums.sum_xy + ratio * ratio * sums.sum_xx).max(0.0) / (sums.samples - 1.0);
+		let remaining_jobs = (total_jobs as f64 - sums.samples).max(0.0);
+		let variance = residual_var * (remaining_jobs + remaining_probe * remaining_probe * sums.samples / (sums.sum_x * sums.sum_x));
+		let value = counted as f64 + ratio * remaining_probe;
+		let margin = 1.96 * variance.sqrt();
+		Some(ProjectedCount {
+			value,
+			low: (value - margin).max(counted as f64),
+			high: value + margin
+		})
+	}
+
+	// "4/3x2x1:samples:sum_y:sum_yy;...", for writing to a resume file
+	pub fn to_header_value(&self) -> String {
+		self.completed.iter()
+			.map(|(features, sums)| format!("{}:{}:{}:{}", features.to_header_key(), sums.samples, sums.sum_y, sums.sum_yy))
+			.collect::<Vec<String>>().join(";")
+	}
+
+	pub fn completed_from_header_value(value: &str) -> Option<BTreeMap<JobFeatures, StratumSums>> {
+		let mut completed = BTreeMap::new();
+		for item in value.split(';').filter(|item| !item.is_empty()) {
+			let fields: Vec<&str> = item.split(':').collect();
+			if fields.len() != 4 {
+				return None;
+			}
+			completed.insert(JobFeatures::from_header_key(fields[0])?, StratumSums {
+				samples: fields[1].parse().ok()?,
+				sum_y: fields[2].parse().ok()?,
+				sum_yy: fields[3].parse().ok()?
+			});
+		}
+		Some(completed)
+	}
+}
+
+// count the features of the jobs that this run has to complete: the
+//   jobs already counted (in a resume file, while checking it), the
+//   jobs in the file for a run that began from a file, and the jobs of
+//   size spawn_n still to be found under the given polycubes (the
+//   single cube for a fresh run, or a resume file's frontier), which
+//   are estimated from random walks rather than enumerated, since
+//   there can be tens of millions of them
+// gives up and returns None if told to stop first
+pub fn take_job_census(spawn_n: u8, counted: BTreeMap<JobFeatures, CensusCount>, job_file: Option<(PathBuf, JobFileFormat)>, found_under: Vec<u128>, atomic_halt: Arc<AtomicBool>) -> Option<BTreeMap<JobFeatures, CensusCount>> {
+	let mut census = counted;
+	if let Some((job_file_path, format)) = job_file {
+		for polycube_enc in JobFileReader::open(&job_file_path, format) {
+			if atomic_halt.load(Ordering::Relaxed) {
+				return None;
+			}
+			census.entry(JobFeatures::of_encoding(polycube_enc)).or_default().jobs += 1.0;
+		}
+	}
+	if !found_under.is_empty() {
+		for (features, census_count) in sample_job_census(&found_under, spawn_n, CENSUS_WALKS, &atomic_halt)? {
+			census.entry(features).or_default().merge(&census_count);
+		}
+	}
+	Some(census)
+}
+
+// estimate the number of polycubes of size spawn_n with each set of
+//   features under the given polycubes, from random walks down to
+//   spawn_n that each start from one of them, chosen uniformly: a walk
+//   adds Knuth's estimate (the number of polycubes it could start from,
+//   times the numbers of children along the way) to the group it ends
+//   in, so each group's mean over all the walks is unbiased
+pub fn sample_job_census(start_encs: &[u128], spawn_n: u8, walks: usize, atomic_halt: &AtomicBool) -> Option<BTreeMap<JobFeatures, CensusCount>> {
+	let mut rng = thread_rng();
+	// the sums of the walks' estimates, and of their squares, by group
+	let mut sums: BTreeMap<JobFeatures, (f64, f64)> = BTreeMap::new();
+	for _ in 0..walks {
+		if atomic_halt.load(Ordering::Relaxed) {
+			return None;
+		}
+		let mut polycube = Polycube::new(false);
+		polycube.rebuild_from_encoding(*start_encs.choose(&mut rng).unwrap());
+		let mut estimate = start_encs.len() as f64;
+		while polycube.n < spawn_n {
+			let children = canonical_children(&mut polycube);
+			estimate *= children.len() as f64;
+			match children.choose(&mut rng) {
+				Some(try_pos) => polycube.add(*try_pos),
+				None => break
+			}
+		}
+		if estimate > 0.0 {
+			let group_sums = sums.entry(JobFeatures::of_polycube(&polycube)).or_default();
+			group_sums.0 += estimate;
+			group_sums.1 += estimate * estimate;
+		}
+	}
+	// (the walks that ended in other groups add 0 to each group's sums)
+	let walks = walks as f64;
+	Some(sums.into_iter().map(|(features, (sum, sum_squares))| {
+		let mean = sum / walks;
+		let walk_variance = ((sum_squares - sum * mean) / (walks - 1.0)).max(0.0);
+		(features, CensusCount { jobs: mean, variance: walk_variance / walks })
+	}).collect())
+}
+
+// call f with the canonical encoding of every polycube of size n
+// the polycubes of size n are found through those of size n-1, so
+//   only a fraction of them are held in memory at once
+// gives up and returns false if halted first
+pub fn for_each_polycube<F>(n: u8, atomic_halt: &Arc<AtomicBool>, mut f: F) -> bool
+		where F: FnMut(u128) {
+	// extend_as_worker() doesn't submit jobs, so these are unused
+	let submit_queue: Arc<ArrayQueue<u128>> = Arc::new(ArrayQueue::new(1));
+	let mut rng = thread_rng();
+	let mut find_encs = |polycube_enc: Option<u128>, limit_n: u8| -> Option<Vec<u128>> {
+		let mut polycube = Polycube::new(polycube_enc.is_none());
+		if let Some(polycube_enc) = polycube_enc {
+			polycube.rebuild_from_encoding(polycube_enc);
+		}
+		let mut encs: Vec<u128> = Vec::new();
+		extend_as_worker(&mut polycube, limit_n, &submit_queue, atomic_halt, &mut rng, true, &mut encs)?;
+		Some(encs)
+	};
+	let parent_encs = match find_encs(None, n.saturating_sub(1).max(1)) {
+		Some(parent_encs) => parent_encs,
+		None => return false
+	};
+	for parent_enc in parent_encs {
+		match find_encs(Some(parent_enc), n) {
+			Some(polycube_encs) => polycube_encs.into_iter().for_each(&mut f),
+			None => return false
+		}
+	}
+	true
+}
+
+// how far a count can be trusted
+// after a halt, the counts up to spawn_n are still exact if the
+//   initial delegator had finished finding those polycubes, or
+//   otherwise up to the size of the smallest polycube in the frontier
+//   it left, while larger counts are partial (or missing, if nothing
+//   of that size was counted yet)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CountStatus {
+	Exact,
+	Partial,
+	Missing
+}
+
+impl CountStatus {
+	pub fn name(&self) -> &'static str {
+		match self {
+			CountStatus::Exact => "exact",
+			CountStatus::Partial => "partial",
+			CountStatus::Missing => "missing"
+		}
+	}
+
+	pub fn from_name(name: &str) -> Option<CountStatus> {
+		[CountStatus::Exact, CountStatus::Partial, CountStatus::Missing].into_iter().find(|status| status.name() == name)
+	}
+
+	// a count that isn't known to be exact is partial if anything
+	//   of that size has been counted
+	pub fn of_inexact_count(count: usize) -> CountStatus {
+		if count > 0 { CountStatus::Partial } else { CountStatus::Missing }
+	}
+}
+
+// after a run, every count not already known to be exact is
+//   exact if the run completed, and otherwise partial or missing
+// a run begun from a polycubes file only counts what descends from
+//   that file's polycubes, which may be one shard of a split, so
+//   its counts are never exact: the sizes below the file's size are
+//   missing, and the rest are partial even when the run completes
+pub fn finish_count_status(count_status: &mut [CountStatus; MAX_N], n: u8, complete: bool, from_begin_file: bool) {
+	for i in 1..n+1 {
+		if complete && !from_begin_file {
+			count_status[i as usize] = CountStatus::Exact;
+		} else if count_status[i as usize] != CountStatus::Exact {
+			count_status[i as usize] = CountStatus::of_inexact_count(unsafe { N_COUNTS[i as usize] });
+		}
+	}
+}
+
+// when set, counting runs write newline-delimited json events to
+//   stdout in place of their human-readable output
+static JSON_EVENTS: AtomicBool = AtomicBool::new(false);
+
+pub fn json_events() -> bool {
+	JSON_EVENTS.load(Ordering::Relaxed)
+}
+
+// write one json event on its own line, as an object with the
+//   event's name and time followed by its fields
+pub fn emit_event(event: &str, fields: serde_json::Value) {
+	let mut object = serde_json::Map::new();
+	object.insert(String::from("event"), serde_json::Value::from(event));
+	object.insert(String::from("time"), serde_json::Value::from(Local::now().to_rfc3339()));
+	if let serde_json::Value::Object(fields) = fields {
+		object.extend(fields);
+	}
+	let stdout = std::io::stdout();
+	let mut out = stdout.lock();
+	writeln!(out, "{}", serde_json::Value::Object(object)).unwrap();
+	out.flush().unwrap();
+}
+
+// the counts so far for each n, for json events
+pub fn counts_to_json(n: u8, count_status: Option<&[CountStatus; MAX_N]>) -> serde_json::Value {
+	serde_json::Value::Array((1..n as usize + 1).map(|i| {
+		let mut count = serde_json::json!({ "n": i, "count": unsafe { N_COUNTS[i] } });
+		if let Some(count_status) = count_status {
+			count["status"] = serde_json::Value::from(count_status[i].name());
+		}
+		count
+	}).collect())
+}
+
+// something that went wrong during a run without stopping it
+pub fn report_warning(message: String) {
+	if json_events() {
+		emit_event("warning", serde_json::json!({ "message": message }));
+	} else {
+		println!("\n{}", message);
+	}
+}
+
+// something that stops a run, reported before exiting
+pub fn report_error(message: String) {
+	if json_events() {
+		emit_event("error", serde_json::json!({ "message": message }));
+	} else {
+		println!("error: {}", message);
+	}
+}
+
+pub fn report_halt(halt_file_path: &Path) {
+	if json_events() {
+		emit_event("halt", serde_json::json!({ "halt_file": halt_file_path.to_string_lossy() }));
+	} else {
+		println!("\nfound halt file [{}], stopping...", halt_file_path.to_str().unwrap());
+	}
+}
+
+pub fn projected_count_to_json(projected_count: Option<ProjectedCount>) -> serde_json::Value {
+	match projected_count {
+		Some(projected_count) => serde_json::json!({
+			"value": projected_count.value,
+			"low": projected_count.low,
+			"high": projected_count.high
+		}),
+		None => serde_json::Value::Null
+	}
+}
+
+pub fn print_results(n: u8, count_status: &[CountStatus; MAX_N]) {
+	let all_exact = (1..n+1).all(|i| count_status[i as usize] == CountStatus::Exact);
+	unsafe {
+		if all_exact {
+			println!("\n\nresults:");
+			for i in 1..n+1 {
+				println!("n = {: >2}: {}", i, N_COUNTS[i as usize]);
+			}
+		} else {
+			println!("\n\npartial results:");
+			for i in 1..n+1 {
+				println!("n = {: >2}: {} ({})", i, N_COUNTS[i as usize], count_status[i as usize].name());
+			}
+		}
+	}
+}
+
+// how a counting run reports its progress and results
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+	// the human-readable output
+	Text,
+	// newline-delimited json events (see emit_event())
+	Json
+}
+
+// the settings for a counting run, as read from a --config file,
+//   given on the command line, or copied into a resume file
+// the keys are named after the count command's flags, and any
+//   setting that isn't given takes the command's default
+// there's nothing to set for sharding, which is done by splitting a
+//   polycubes file and counting each part with begin_from_file, or for
+//   search statistics, which are chosen when building (see the
+//   instrument feature)
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RunConfig {
+	pub n: Option<u8>,
+	pub threads: Option<u8>,
+	pub spawn_n: Option<u8>,
+	pub begin_from_file: Option<PathBuf>,
+	pub write_found_polycubes_file: Option<String>,
+	pub work_dir: Option<PathBuf>,
+	// write a resume file this often and carry on from it, rather
+	//   than only when halted
+	pub checkpoint_minutes: Option<u32>,
+	pub canonicalizer: Option<String>,
+	pub output_format: Option<OutputFormat>
+}
+
+impl RunConfig {
+	// read a .toml config file, where relative paths are taken to be
+	//   relative to the config file's directory
+	pub fn from_file(path: &Path) -> Result<RunConfig, String> {
+		let contents = std::fs::read_to_string(path)
+			.map_err(|err| format!("failed to read config file [{}]: {}", path.to_string_lossy(), err))?;
+		let mut config: RunConfig = toml::from_str(&contents)
+			.map_err(|err| format!("invalid config file [{}]: {}", path.to_string_lossy(), err.message()))?;
+		let dir = path.parent().unwrap_or(Path::new(""));
+		config.begin_from_file = config.begin_from_file.map(|file| dir.join(file));
+		config.write_found_polycubes_file = config.write_found_polycubes_file.map(|file| dir.join(file).to_string_lossy().to_string());
+		// (and the work directory is made absolute, as for --work-dir)
+		config.work_dir = config.work_dir.map(|work_dir| std::path::absolute(dir.join(&work_dir)).unwrap_or(work_dir));
+		Ok(config)
+	}
+
+	// the settings in other, with any it doesn't have taken from self
+	pub fn overridden_by(self, other: RunConfig) -> RunConfig {
+		RunConfig {
+			n: other.n.or(self.n),
+			threads: other.threads.or(self.threads),
+			spawn_n: other.spawn_n.or(self.spawn_n),
+			begin_from_file: other.begin_from_file.or(self.begin_from_file),
+			write_found_polycubes_file: other.write_found_polycubes_file.or(self.write_found_polycubes_file),
+			work_dir: other.work_dir.or(self.work_dir),
+			checkpoint_minutes: other.checkpoint_minutes.or(self.checkpoint_minutes),
+			canonicalizer: other.canonicalizer.or(self.canonicalizer),
+			output_format: other.output_format.or(self.output_format)
+		}
+	}
+
+	pub fn json_output(&self) -> bool {
+		self.output_format == Some(OutputFormat::Json)
+	}
+
+	// split the settings taken from the command line into those that
+	//   came from environment variables and those given as flags, since
+	//   a config file's or resume file's settings override the former
+	pub fn split_env_settings(self, matches: &ArgMatches) -> (RunConfig, RunConfig) {
+		let from_env = |id: &str| matches.value_source(id) == Some(ValueSource::EnvVariable);
+		let env_config = RunConfig {
+			threads: self.threads.filter(|_| from_env("threads")),
+			spawn_n: self.spawn_n.filter(|_| from_env("spawn_n")),
+			canonicalizer: self.canonicalizer.clone().filter(|_| from_env("canonicalizer")),
+			..RunConfig::default()
+		};
+		let flag_config = RunConfig {
+			threads: self.threads.filter(|_| !from_env("threads")),
+			spawn_n: self.spawn_n.filter(|_| !from_env("spawn_n")),
+			canonicalizer: self.canonicalizer.filter(|_| !from_env("canonicalizer")),
+			..self
+		};
+		(env_config, flag_config)
+	}
+
+	// check the settings the same way the command line is checked,
+	//   since those from a config file haven't been yet
+	pub fn validate(&self) -> Result<(), String> {
+		match self.n {
+			Some(n) if (2..=21).contains(&n) => {}
+			Some(_) => return Err(String::from("<n> must be greater than 1 and no more than 21")),
+			None => return Err(String::from("<n> must be given"))
+		}
+		if let Some(threads) = self.threads {
+			parse_count_threads(&threads.to_string()).map_err(|err| format!("<threads> {}", err))?;
+		}
+		if self.spawn_n.is_some_and(|spawn_n| spawn_n < 4) {
+			return Err(String::from("<spawn-n> must be greater than 3"));
+		}
+		if let Some(begin_file) = self.begin_from_file.as_ref() {
+			if self.spawn_n.is_some() {
+				return Err(String::from("cannot specify both a <spawn-n> and a <begin-file>"));
+			}
+			validate_resume_file_arg(&begin_file.to_string_lossy(), "<begin-file>")?;
+		}
+		if let Some(polycubes_file) = self.write_found_polycubes_file.as_ref() {
+			validate_polycubes_file_arg(polycubes_file, "<polycubes-file>")?;
+		}
+		if let Some(work_dir) = self.work_dir.as_ref() {
+			validate_work_dir_arg(&work_dir.to_string_lossy())?;
+		}
+		if self.checkpoint_minutes == Some(0) {
+			return Err(String::from("<checkpoint-minutes> must be greater than 0"));
+		}
+		if let Some(name) = self.canonicalizer.as_ref() {
+			if !CANONICALIZERS.iter().any(|c| c.name() == name) {
+				return Err(format!("unknown canonicalizer [{}], expected one of: {}",
+					name, CANONICALIZERS.iter().map(|c| c.name()).collect::<Vec<&str>>().join(", ")));
+			}
+		}
+		Ok(())
+	}
+
+	// the settings as a one-line toml inline table, for a resume file header
+	pub fn to_header_value(&self) -> String {
+		toml::Value::try_from(self).unwrap().to_string()
+	}
+
+	pub fn from_header_value(value: &str) -> Option<RunConfig> {
+		#[derive(Deserialize)]
+		struct Header {
+			config: RunConfig
+		}
+		toml::from_str::<Header>(&format!("config = {}", value)).ok().map(|header| header.config)
+	}
+}
+
+// everything needed for one counting run
+pub struct RunSettings {
+	pub n: u8,
+	pub threads: u8,
+	pub spawn_n: u8,
+	pub resume_file: Option<PathBuf>,
+	pub begin_file: Option<PathBuf>,
+	pub polycubes_file: Option<String>,
+	// the settings as given, which are copied into the resume file
+	//   so that resuming carries on with the same setup
+	pub config: RunConfig,
+	// when set, creating this file halts the run, and any jobs
+	//   not yet evaluated are then written to a resume file
+	pub halt_file: Option<PathBuf>,
+	// print progress and status messages while running
+	pub verbose: bool
+}
+
+pub struct RunOutcome {
+	// when resuming, n is read from the resume file
+	pub n: u8,
+	pub complete: bool,
+	// whether each count is exact, partial or missing
+	pub count_status: [CountStatus; MAX_N],
+	// the projected final count at n, if the run didn't complete
+	pub projected_count: Option<ProjectedCount>,
+	// seconds spent on this run, up to when the last count came in
+	pub elapsed_sec: f64,
+	// seconds spent on earlier runs, when resuming
+	pub previous_total_elapsed_sec: f64,
+	// the resume file written when the run was halted or reached a
+	//   checkpoint, if there was anything left to write to one
+	pub resume_file: Option<PathBuf>,
+	// whether the run was only stopped to write a checkpoint, so it can
+	//   carry on from the resume file
+	pub checkpoint: bool
+}
+
+// count polycubes up to size n, leaving the counts in N_COUNTS
+pub fn run_count(settings: &RunSettings) -> RunOutcome {
+	let mut arg_n = settings.n;
+	let arg_threads = settings.threads;
+	let mut arg_spawn_n = settings.spawn_n;
+	let arg_resume_file = settings.resume_file.as_ref();
+	let arg_begin_file = settings.begin_file.as_ref();
+	// (a resumed run keeps the begin file in its saved config)
+	let from_begin_file = settings.config.begin_from_file.is_some();
+	let arg_polycubes_file = settings.polycubes_file.as_ref();
+	let arg_polycubes_file_path: &str = settings.polycubes_file.as_deref().unwrap_or("none");
+	// start counting from zero, in case this isn't the first run
+	unsafe {
+		N_COUNTS = [0; 23];
+	}
+	let mut previous_total_elapsed_sec: f64 = 0.0;
+	let mut complete = false;
+	// sizes whose counts are known to be exact even if the run
+	//   doesn't complete, and after the run, the status of every count
+	let mut count_status = [CountStatus::Missing; MAX_N];
+	let mut projected_count: Option<ProjectedCount> = None;
+	let mut resume_file: Option<PathBuf> = None;
+	let mut checkpoint = false;
+	let start_time = Instant::now();
+	let mut last_count_increment_time: Option<Instant> = None;
+	if arg_threads == 0 {
+		let mut polycube = Polycube::new(true);
+		extend_single_thread(&mut polycube, arg_n, 0);
+		polycube.record_search_stats();
+		flush_search_stats();
+		complete = true;
+	} else {
+		// begin from <resume-file>, <begin-file>, or from scratch with no polycubes
+		// the jobs in a file are streamed to the workers as they are
+		//   needed, so here we only read the file's header
+		// a resume file's census is taken while checking it
+		let mut resume_census: Option<BTreeMap<JobFeatures, CensusCount>> = None;
+		let mut job_reader: Option<JobFileReader> = match arg_resume_file {
+			Some(resume_file_path) => {
+				// the jobs are all rebuilt and checked before any are counted
+				let check = check_resume_file(resume_file_path);
+				if !check.problems.is_empty() {
+					report_error(format!("not resuming from a resume file with {} problem(s)", check.problems.len()));
+					exit(EXIT_ERROR);
+				}
+				resume_census = Some(check.census);
+				let reader = JobFileReader::open(resume_file_path, JobFileFormat::Resume);
+				arg_n = reader.header.n;
+				arg_spawn_n = reader.header.spawn_n;
+				previous_total_elapsed_sec = reader.header.previous_total_elapsed_sec;
+				unsafe {
+					for (i, count) in reader.header.n_counts.iter() {
+						if *count > 0 && settings.verbose {
+							println!("    n = {: >2}: {}", i, count);
+						}
+						N_COUNTS[*i as usize] = *count;
+					}
+				}
+				// resume files written before the status was recorded
+				//   have exact counts up to spawn_n, unless they were
+				//   begun from a file, when none of their counts are
+				if reader.header.count_status.is_empty() && !from_begin_file {
+					for i in 1..arg_spawn_n+1 {
+						count_status[i as usize] = CountStatus::Exact;
+					}
+				}
+				for (i, status) in reader.header.count_status.iter() {
+					if !from_begin_file || *status != CountStatus::Exact {
+						count_status[*i as usize] = *status;
+					}
+				}
+				if settings.verbose {
+					println!("found {} polycubes in resume file", reader.job_count);
+				}
+				Some(reader)
+			}
+			None => {
+				match arg_begin_file {
+					Some(begin_file_path) => {
+						let reader = JobFileReader::open(begin_file_path, JobFileFormat::Polycubes);
+						if settings.verbose {
+							println!("found {} polycubes in polycubes file", reader.job_count);
+						}
+						Some(reader)
+					}
+					None => {
+						None
+					}
+				}
+			}
+		};
+		let job_file_count: usize = job_reader.as_ref().map_or(0, |reader| reader.job_count);
+		// only the first job is rebuilt here, to check its size: the
+		//   rest are rebuilt by the workers as they take them off the queue
+		let job_n: u8 = match job_reader.as_mut().and_then(|reader| reader.peek()) {
+			Some(polycube_enc) => {
+				let mut p = Polycube::new(false);
+				p.rebuild_from_encoding(polycube_enc);
+				p.n
+			}
+			None => 0
+		};
+		if job_file_count > 0 && job_n >= arg_n {
+			report_error(String::from("n must be larger than the polycubes present in the <resume-file> or <begin-file>"));
+			exit(EXIT_ERROR);
+		}
+
+		let mut saved_worker_jobs: usize = 0;
+		// resume files record the jobs the run started with, and
+		//   how many of them were completed
+		let resume_header = job_reader.as_ref().filter(|_| arg_resume_file.is_some()).map(|reader| &reader.header);
+		let total_worker_jobs = match (resume_header.and_then(|header| header.total_jobs), arg_spawn_n) {
+			(Some(total_jobs), _) => total_jobs,
+			(None, 0) => job_file_count,
+			(None, _) => WELL_KNOWN_N_COUNTS[arg_spawn_n as usize]
+		};
+		let resume_frontier: Vec<u128> = resume_header.map_or(Vec::new(), |header| header.frontier.clone());
+		let mut compl_worker_jobs: isize = match resume_header {
+			Some(header) => {
+				header.completed_jobs.unwrap_or(total_worker_jobs - job_file_count).try_into().unwrap()
+			}
+			None => 0
+		};
+		// (for the rate of jobs completed in this run)
+		let start_compl_worker_jobs = compl_worker_jobs;
+		let mut last_progress_event = Instant::now();
+		// resume files carry the cost model, so estimates start out calibrated
+		let mut cost_model = JobCostModel::new(arg_n, arg_spawn_n, arg_begin_file.is_none());
+		if let Some(saved_cost_model) = resume_header.and_then(|header| header.cost_model) {
+			if saved_cost_model.probe_n == cost_model.probe_n {
+				cost_model = saved_cost_model;
+			}
+		}
+		// seconds that the workers have spent busy, sampled every second,
+		//   for the parallelism (see Parallelism)
+		let mut busy_worker_sec: f64 = 0.0;
+		let mut sampled_sec: f64 = 0.0;
+		// the final count at n is projected from the completed jobs' counts,
+		//   either by their probes or once a census of the jobs to
+		//   complete has been taken (see CountProjection)
+		let mut count_projection = CountProjection::new();
+		if let Some(completed) = resume_header.and_then(|header| header.projection_strata.clone()) {
+			count_projection.completed = completed;
+		}
+		// (the probes are only comparable with the same probe_n)
+		if resume_header.and_then(|header| header.cost_model.as_ref()).is_some_and(|saved_cost_model| saved_cost_model.probe_n == cost_model.probe_n) {
+			if let Some(projection_ratio) = resume_header.and_then(|header| header.projection_ratio) {
+				count_projection.by_probe = projection_ratio;
+			}
+		}
+		let census_stop = Arc::new(AtomicBool::new(false));
+		let mut census_proc: Option<JoinHandle<Option<BTreeMap<JobFeatures, CensusCount>>>> = {
+			let census_job_file = match (arg_resume_file, arg_begin_file) {
+				(None, Some(path)) => Some((path.clone(), JobFileFormat::Polycubes)),
+				_ => None
+			};
+			let counted = resume_census.take().unwrap_or_default();
+			// the jobs still to be found by the initial delegator
+			let census_found_under: Vec<u128> = match (arg_resume_file, arg_begin_file) {
+				(None, None) => vec![Polycube::new(true).find_canonical_info(None).enc],
+				_ => resume_frontier.clone()
+			};
+			let cs = census_stop.clone();
+			Some(thread::spawn(move || take_job_census(arg_spawn_n, counted, census_job_file, census_found_under, cs)))
+		};
+		if let Some(halt_file_path) = settings.halt_file.as_ref().filter(|_| settings.verbose) {
+			println!("to halt early, create the file [{}]", halt_file_path.to_str().unwrap());
+		}
+		let mut polycubes_to_write_to_resume_file: Vec<u128> = Vec::new();
+		let mut polycubes_to_write_to_polycubes_file: Vec<u128> = Vec::new();
+		// jobs that panicked on every attempt, with the panic message
+		let mut quarantined_jobs: Vec<(u128, String)> = Vec::new();
+		// set if a thread died outside of a job, in which case
+		//   the counts can't be trusted to be complete
+		let mut thread_failed = false;
+
+		// these are the canonical encodings of the found Polycubes
+		//   of whatever size that the child processes will evaluate
+		// the queue is kept small: whichever thread submits the jobs
+		//   waits for the workers when the queue is full
+		let submit_queue: Arc<ArrayQueue<u128>> =
+			Arc::new(ArrayQueue::new(JOB_QUEUE_CAPACITY));
+		// the child processes will return both counts for fully-
+		//   evaluated Polycubes and also non-evaluated Polycubes
+		//   to write to disk for continuing later
+		let response_queue: Arc<ArrayQueue<ThreadResponse>> =
+			Arc::new(ArrayQueue::new(RESPONSE_QUEUE_CAPACITY));
+
+
+		// bool for signalling that an early halt has been requested
+		let atomic_halt = Arc::new(AtomicBool::new(false));
+		// bool for signalling to the workers to stop looking for jobs
+		let atomic_done = Arc::new(AtomicBool::new(false));
+
+		let mut initial_workers_to_spawn = arg_threads;
+		if arg_resume_file.is_none() && arg_begin_file.is_none() {
+			// initially spawn threads-1 worker threads, plus one
+			//   thread for the initial work delegator
+			initial_workers_to_spawn -= 1
+		}
+		// when resuming or beginning from a file, a lightweight thread feeds
+		//   the jobs into the queue, and it isn't counted as a worker
+		let mut feeder_proc: Option<JoinHandle<JobFileReader>> = None;
+		// the initial delegator starts from the single cube, or from a
+		//   resume file's frontier, in which case it runs alongside the
+		//   feeder and isn't counted as a worker either
+		let delegator_start_encs: Vec<u128> = if let Some(reader) = job_reader.take() {
+			if arg_begin_file.is_some() {
+				unsafe {
+					N_COUNTS[job_n as usize] = job_file_count;
+				}
+			}
+			let ah = atomic_halt.clone();
+			let sq = submit_queue.clone();
+			feeder_proc = Some(thread::spawn(move || {
+				feed_jobs_outer(reader, ah, sq)
+			}));
+			resume_frontier
+		} else {
+			unsafe {
+				N_COUNTS[1] = 1;
+			}
+			vec![Polycube::new(true).find_canonical_info(None).enc]
+		};
+		let delegator_proc: Option<JoinHandle<Vec<u128>>> = if delegator_start_encs.is_empty() {
+			None
+		} else {
+			if settings.verbose && feeder_proc.is_some() {
+				println!("finding the jobs under the {} polycubes of the resume file's frontier", delegator_start_encs.len());
+			}
+			let ah = atomic_halt.clone();
+			let sq = submit_queue.clone();
+			let rq = response_queue.clone();
+			Some(thread::spawn(move || {
+				extend_and_delegate_outer(delegator_start_encs, arg_n, ah, sq, rq, arg_spawn_n)
+			}))
+		};
+		let mut worker_handles: Vec<JoinHandle<()>> = Vec::new();
+		let mut worker_waiting_bools: Vec<Arc<AtomicBool>> = Vec::new();
+		let arg_write_polycubes_file: bool = arg_polycubes_file.is_some();
+		for _i in 0..initial_workers_to_spawn {
+			let ah = atomic_halt.clone();
+			let ad = atomic_done.clone();
+			let aw = Arc::new(AtomicBool::new(false));
+			let awc = aw.clone();
+			worker_waiting_bools.push(aw);
+			let sq = submit_queue.clone();
+			let rq = response_queue.clone();
+			let handle = thread::spawn(move || {
+				extend_as_worker_outer(arg_n, ah, ad, awc, sq, rq, arg_write_polycubes_file);
+			});
+			worker_handles.push(handle);
+		}
+		let mut halted = false;
+		let mut delegator_finished = false;
+		let mut last_stats_and_halt = Instant::now();
+		// after a halt, keep going until every thread has finished and
+		//   all their responses have been collected
+		while !halted
+				|| worker_handles.iter().any(|w| !w.is_finished())
+				|| delegator_proc.as_ref().is_some_and(|d| !d.is_finished())
+				|| feeder_proc.as_ref().is_some_and(|f| !f.is_finished())
+				|| !response_queue.is_empty() {
+			if census_proc.as_ref().is_some_and(|c| c.is_finished()) {
+				match census_proc.take().unwrap().join() {
+					Ok(census) => count_projection.census = census,
+					Err(_) => report_warning(String::from("error: the thread taking a census of the jobs panicked, so the final count won't be projected"))
+				}
+			}
+			// once the initial work delegator has finished,
+			//   spawn a new worker thread
+			if !delegator_finished && delegator_proc.is_some()
+					&& delegator_proc.as_ref().unwrap().is_finished() {
+				delegator_finished = true;
+				// the initial delegator thread submits its results through
+				//   the same response_queue as the rest of the workers, but it shouldn't
+				//   be counted as a completed worker job
+				compl_worker_jobs -= 1;
+				if settings.verbose && !halted && feeder_proc.is_some() {
+					println!("\nall the jobs under the resume file's frontier have been found");
+				}
+			}
+			if !halted && delegator_finished && worker_handles.len() < arg_threads as usize {
+				if settings.verbose {
+					println!("\ninitial delegator thread has finished, spawning a new worker thread");
+				}
+				let ah = atomic_halt.clone();
+				let ad = atomic_done.clone();
+				let aw = Arc::new(AtomicBool::new(false));
+				let awc = aw.clone();
+				worker_waiting_bools.push(aw);
+				let sq = submit_queue.clone();
+				let rq = response_queue.clone();
+				let handle = thread::spawn(move || {
+					extend_as_worker_outer(arg_n, ah, ad, awc, sq, rq, arg_write_polycubes_file);
+				});
+				worker_handles.push(handle);
+			}
+			// check for halt file
+			if !halted && settings.halt_file.as_ref().is_some_and(|halt_file_path| halt_file_path.exists()) {
+				report_halt(settings.halt_file.as_ref().unwrap());
+				// signal to the threads that they should stop
+				atomic_halt.store(true, Ordering::Relaxed);
+				halted = true;
+			}
+			// check if everything has completed
+			if !halted && (delegator_proc.is_none() || delegator_proc.as_ref().unwrap().is_finished())
+					&& (feeder_proc.is_none() || feeder_proc.as_ref().unwrap().is_finished())
+					&& submit_queue.is_empty() {
+				// a worker that has died can't be waiting, but it isn't
+				//   working on anything either
+				let all_workers_waiting = worker_waiting_bools.iter().zip(worker_handles.iter())
+					.all(|(w, handle)| w.load(Ordering::Acquire) || handle.is_finished());
+				if all_workers_waiting {
+					if settings.verbose {
+						println!("\nlooks like we have finished!  stopping...");
+					}
+					// signal to the threads that they should stop
+					atomic_done.store(true, Ordering::Relaxed);
+					halted = true;
+					complete = true;
+				}
+			}
+
+			// regardless of halt file, continue to process responses
+			//   sent by the workers (if any are currently available)
+			let mut found_something = true;
+			while found_something {
+				if last_stats_and_halt.elapsed().as_secs_f32() > 1.0 {
+					let sample_sec = last_stats_and_halt.elapsed().as_secs_f64();
+					last_stats_and_halt = Instant::now();
+					let busy_workers = worker_waiting_bools.iter().zip(worker_handles.iter())
+						.filter(|(w, handle)| !w.load(Ordering::Acquire) && !handle.is_finished())
+						.count();
+					busy_worker_sec += busy_workers as f64 * sample_sec;
+					sampled_sec += sample_sec;
+					// check for halt file
+					if !halted && !complete && settings.halt_file.as_ref().is_some_and(|halt_file_path| halt_file_path.exists()) {
+						report_halt(settings.halt_file.as_ref().unwrap());
+						// signal to the threads that they should stop
+						atomic_halt.store(true, Ordering::Relaxed);
+						halted = true;
+					}
+					// a checkpoint halts the run in the same way, and the
+					//   caller then carries on from the resume file
+					if !halted && !complete && settings.halt_file.is_some()
+							&& settings.config.checkpoint_minutes.is_some_and(|minutes| start_time.elapsed() >= Duration::from_secs(minutes as u64 * 60)) {
+						if settings.verbose {
+							println!("\nstopping to write a checkpoint...");
+						}
+						atomic_halt.store(true, Ordering::Relaxed);
+						halted = true;
+						checkpoint = true;
+					}
+					// print stats
+					// (progress and the ETA are weighted by the estimated cost of
+					//   each job, see JobCostModel)
+					let time_elapsed = start_time.elapsed().as_secs_f64();
+					let remaining_jobs = (total_worker_jobs as isize - compl_worker_jobs).max(0) as usize;
+					let parallelism = Parallelism::new(arg_threads as usize, remaining_jobs, busy_worker_sec, sampled_sec);
+					if settings.verbose && compl_worker_jobs > 0 {
+						if let Some(estimate) = cost_model.estimate(total_worker_jobs, compl_worker_jobs, &parallelism) {
+							let total_seconds = estimate.remaining_sec + time_elapsed + previous_total_elapsed_sec;
+							let projected = match count_projection.project(unsafe { N_COUNTS[arg_n as usize] }, cost_model.remaining_probe(total_worker_jobs, compl_worker_jobs), total_worker_jobs) {
+								Some(projection) => format!(", projected:[{}]", projection.describe()),
+								None => String::new()
+							};
+							print!("    {:.4}% complete, ETA:[{}] (95%:[{} to {}]), total:[{}], counting for n={}:[{}]{}, outstanding threads:[{}-{}={}]        \r",
+								estimate.fraction_complete * 100.0,
+								seconds_to_dur(estimate.remaining_sec),
+								seconds_to_dur(estimate.remaining_sec_low),
+								seconds_to_dur(estimate.remaining_sec_high),
+								seconds_to_dur(total_seconds),
+								arg_n,
+								unsafe { N_COUNTS[arg_n as usize] },
+								projected,
+								total_worker_jobs,
+								compl_worker_jobs,
+								total_worker_jobs as isize - compl_worker_jobs);
+							std::io::stdout().flush().unwrap();
+						}
+					}
+					if json_events() && compl_worker_jobs > 0 && last_progress_event.elapsed().as_secs_f64() >= PROGRESS_EVENT_SEC {
+						last_progress_event = Instant::now();
+						let estimate = cost_model.estimate(total_worker_jobs, compl_worker_jobs, &parallelism);
+						emit_event("progress", serde_json::json!({
+							"n": arg_n,
+							"completed_jobs": compl_worker_jobs,
+							"total_jobs": total_worker_jobs,
+							"fraction_complete": estimate.as_ref().map(|estimate| estimate.fraction_complete),
+							"counts": counts_to_json(arg_n, None),
+							"projected_count": projected_count_to_json(count_projection.project(unsafe { N_COUNTS[arg_n as usize] }, cost_model.remaining_probe(total_worker_jobs, compl_worker_jobs), total_worker_jobs)),
+							"jobs_per_sec": (compl_worker_jobs - start_compl_worker_jobs) as f64 / time_elapsed,
+							"elapsed_sec": time_elapsed + previous_total_elapsed_sec,
+							"eta_sec": estimate.as_ref().map(|estimate| estimate.remaining_sec),
+							"eta_sec_low": estimate.as_ref().map(|estimate| estimate.remaining_sec_low),
+							"eta_sec_high": estimate.as_ref().map(|estimate| estimate.remaining_sec_high)
+						}));
+					}
+				}
+				let response = response_queue.pop();
+				if response.is_none() {
+					found_something = false;
+					continue;
+				}
+				let response = response.unwrap();
+				if response.job_complete {
+					// we have a completed job
+					//   (either a fully-evaluated polycube or a non-evaluated polycube)
+					//   so we can increment the completed worker jobs count
+					compl_worker_jobs += 1;
+					// if we have a fully-evaluated polycube, we can increment the
+					//   count for that polycube's n
+					if response.results.is_some() {
+						let results = response.results.unwrap();
+						unsafe {
+							for i in 1..arg_n+1 {
+								N_COUNTS[i as usize] += results[i as usize];
+							}
+						}
+						// (the initial delegator's response has no job time)
+						if let Some(job_sec) = response.job_sec {
+							cost_model.cost.add(cost_model.probe(&results), job_sec);
+						}
+						if let Some(features) = response.job_features {
+							count_projection.add(features, results[arg_n as usize], cost_model.probe(&results));
+						}
+					}
+					last_count_increment_time = Some(Instant::now());
+					if response.final_polycube_encs.len() > 0 {
+						polycubes_to_write_to_polycubes_file.extend(response.final_polycube_encs);
+						if polycubes_to_write_to_polycubes_file.len() >= 10000 {
+							write_polycubes_file(arg_n, arg_polycubes_file_path, &polycubes_to_write_to_polycubes_file);
+							polycubes_to_write_to_polycubes_file.clear();
+						}
+					} else if arg_write_polycubes_file {
+						report_warning(String::from("response without any final polycubes!"));
+					}
+				} else if let Some(message) = response.failure {
+					// the job panicked on every attempt, so it isn't counted,
+					//   but it's kept so it can be evaluated again later
+					quarantined_jobs.push((response.orig_polycube_enc.unwrap(), message));
+				} else {
+					match response.orig_polycube_enc {
+						// we have a non-evaluated polycube, so we can write it to disk
+						Some(polycube_enc) => {
+							polycubes_to_write_to_resume_file.push(polycube_enc);
+							saved_worker_jobs += 1;
+						}
+						None => {
+							report_warning(String::from("the initial delegator worker was halted.  thus, we cannot resume from this point later and will not write any data to disk"))
+						}
+					}
+				}
+			}
+			thread::sleep(Duration::from_millis(1000));
+		}
+		// jobs are evaluated under catch_unwind, so a thread only dies
+		//   here if it panicked outside of a job, and whatever it was
+		//   responsible for is then lost
+		for w in worker_handles.into_iter() {
+			if w.join().is_err() {
+				report_warning(String::from("error: a worker thread panicked outside of a job"));
+				thread_failed = true;
+			}
+		}
+		// the initial delegator counts every polycube up to spawn_n,
+		//   except those under the frontier it leaves if halted, so the
+		//   counts up to the size of its smallest polycube are exact
+		let mut frontier: Vec<u128> = Vec::new();
+		if let Some(delegator) = delegator_proc {
+			match delegator.join() {
+				Ok(delegator_frontier) => {
+					frontier = delegator_frontier;
+					let exact_n = frontier.iter().map(|polycube_enc| {
+						let mut p = Polycube::new(false);
+						p.rebuild_from_encoding(*polycube_enc);
+						p.n
+					}).min().unwrap_or(arg_spawn_n).min(arg_spawn_n);
+					for i in 1..exact_n+1 {
+						count_status[i as usize] = CountStatus::Exact;
+					}
+				}
+				Err(_) => {
+					report_warning(String::from("error: the initial delegator thread panicked, so some jobs were never submitted"));
+					thread_failed = true;
+					count_status = [CountStatus::Missing; MAX_N];
+				}
+			}
+		}
+		// jobs the feeder thread never got to submit, and any jobs
+		//   the delegator or feeder submitted after the workers had
+		//   already drained the queue, also need to be saved
+		let mut unsubmitted_jobs: Option<JobFileReader> = None;
+		if let Some(feeder) = feeder_proc {
+			match feeder.join() {
+				Ok(reader) => {
+					if reader.remaining() > 0 {
+						saved_worker_jobs += reader.remaining();
+						unsubmitted_jobs = Some(reader);
+					}
+				}
+				Err(_) => {
+					report_warning(String::from("error: the thread feeding jobs from the file panicked, so some jobs were never submitted"));
+					thread_failed = true;
+				}
+			}
+		}
+		while let Some(polycube_enc) = submit_queue.pop() {
+			polycubes_to_write_to_resume_file.push(polycube_enc);
+			saved_worker_jobs += 1;
+		}
+		if !quarantined_jobs.is_empty() {
+			write_quarantine_file(arg_n, settings.config.work_dir.as_deref(), &quarantined_jobs);
+			// the quarantined jobs also go in the resume file, so
+			//   the run can be finished once the cause is fixed
+			polycubes_to_write_to_resume_file.extend(quarantined_jobs.iter().map(|(polycube_enc, _message)| *polycube_enc));
+			saved_worker_jobs += quarantined_jobs.len();
+		}
+		if !quarantined_jobs.is_empty() || thread_failed {
+			complete = false;
+		}
+		finish_count_status(&mut count_status, arg_n, complete, from_begin_file);
+		// a census that's still being taken isn't worth waiting for
+		if let Some(census) = census_proc {
+			census_stop.store(true, Ordering::Relaxed);
+			if let Ok(census) = census.join() {
+				count_projection.census = census;
+			}
+		}
+		if !complete {
+			projected_count = count_projection.project(unsafe { N_COUNTS[arg_n as usize] }, cost_model.remaining_probe(total_worker_jobs, compl_worker_jobs), total_worker_jobs);
+		}
+		if settings.verbose {
+			println!("\nall threads have completed: compl_worker_jobs={}, saved_worker_jobs={} (compl+saved={}, where @n={} should be total_worker_jobs={})",
+				compl_worker_jobs, saved_worker_jobs, compl_worker_jobs + saved_worker_jobs as isize, arg_spawn_n, total_worker_jobs);
+		}
+		if settings.halt_file.is_some() && (!polycubes_to_write_to_resume_file.is_empty() || unsubmitted_jobs.is_some() || !frontier.is_empty()) {
+			resume_file = Some(write_resume_file(
+				arg_n,
+				arg_spawn_n,
+				polycubes_to_write_to_resume_file,
+				unsubmitted_jobs,
+				&RunProgress {
+					elapsed_sec: previous_total_elapsed_sec + (last_count_increment_time.unwrap_or(start_time).duration_since(start_time).as_secs_f64()),
+					completed_jobs: compl_worker_jobs.max(0) as usize,
+					total_jobs: total_worker_jobs,
+					count_status,
+					cost_model,
+					count_projection: &count_projection,
+					projected_count,
+					config: &settings.config,
+					frontier: &frontier
+				},
+			));
+		}
+		if polycubes_to_write_to_polycubes_file.len() > 0 {
+			write_polycubes_file(arg_n, arg_polycubes_file_path, &polycubes_to_write_to_polycubes_file);
+		}
+	}
+	if last_count_increment_time.is_none() {
+		last_count_increment_time = Some(Instant::now());
+	}
+	finish_count_status(&mut count_status, arg_n, complete, from_begin_file);
+	RunOutcome {
+		n: arg_n,
+		complete,
+		count_status,
+		projected_count,
+		elapsed_sec: last_count_increment_time.unwrap().duration_since(start_time).as_secs_f64(),
+		previous_total_elapsed_sec,
+		resume_file,
+		checkpoint
+	}
+}
+
+// one entry in the benchmark matrix
+pub struct BenchConfig {
+	pub n: u8,
+	pub threads: u8,
+	pub spawn_n: u8
+}
+
+// the settings the readme's running time tables are made with, where
+//   the multi-threaded run uses all available cores
+pub fn bench_matrix(quick: bool) -> Vec<BenchConfig> {
+	let threads = match thread::available_parallelism() {
+		Ok(parallelism) => parallelism.get().clamp(2, u8::MAX as usize) as u8,
+		Err(_) => 2
+	};
+	if quick {
+		return vec![
+			BenchConfig { n: 9, threads: 0, spawn_n: 0 },
+			BenchConfig { n: 10, threads: 0, spawn_n: 0 },
+			BenchConfig { n: 10, threads, spawn_n: 6 }];
+	}
+	vec![
+		BenchConfig { n: 10, threads: 0, spawn_n: 0 },
+		BenchConfig { n: 11, threads: 0, spawn_n: 0 },
+		BenchConfig { n: 11, threads, spawn_n: 7 }]
+}
+
+// the commit this binary was built from, with "-dirty" appended if
+//   the working tree had changes at the time the benchmark was run
+pub fn git_commit() -> String {
+	let git = |args: &[&str]| -> Option<String> {
+		let output = std::process::Command::new("git")
+			.arg("-C").arg(env!("CARGO_MANIFEST_DIR"))
+			.args(args)
+			.output().ok()?;
+		if !output.status.success() {
+			return None;
+		}
+		Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+	};
+	match git(&["rev-parse", "--short", "HEAD"]) {
+		Some(commit) => match git(&["status", "--porcelain", "--untracked-files=no"]) {
+			Some(status) if !status.is_empty() => format!("{}-dirty", commit),
+			_ => commit
+		},
+		None => String::from("unknown")
+	}
+}
+
+pub fn cpu_model() -> String {
+	if let Ok(cpuinfo) = std::fs::read_to_string("/proc/cpuinfo") {
+		for line in cpuinfo.lines() {
+			if let Some((key, value)) = line.split_once(':') {
+				if key.trim() == "model name" || key.trim() == "Model" {
+					return value.trim().to_string();
 				}
 			}
-
 		}
 	}
-	// after halt, drain the queue
-	let mut found_something = true;
-	while found_something {
-		let polycube = submit_queue.pop();
-		// put a message here to indicate that this Polycube is
-		//   unevaluated
-		if polycube.is_none() {
-			found_something = false;
-			continue;
-		}
-		match response_queue.push(ThreadResponse{
-				job_complete: false,
-				results: None,
-				orig_polycube: polycube,
-				final_polycube_encs: Vec::new() }) {
-			Ok(_) => {}
-			Err(_) => {
-				panic!("response_queue.push() failed");
-			}
+	if let Ok(output) = std::process::Command::new("sysctl").args(["-n", "machdep.cpu.brand_string"]).output() {
+		if output.status.success() {
+			return String::from_utf8_lossy(&output.stdout).trim().to_string();
 		}
 	}
+	String::from("unknown")
+}
 
-	//except HaltSignal:
-	//	# maybe indicate that the initial delegator worker was
-	//	#   halted with a special-case None value here
-	//	response_queue.put((False, None))
+// run each config in the matrix (after some untimed warmup runs) a
+//   number of times, print a summary, and return the results as json
+pub fn run_bench(matrix: &[BenchConfig], warmup: usize, repetitions: usize) -> serde_json::Value {
+	let mut results: Vec<serde_json::Value> = Vec::new();
+	for config in matrix {
+		println!("\nbenchmarking n={} threads={} spawn_n={} ({} warmup, {} repetitions)",
+			config.n, config.threads, config.spawn_n, warmup, repetitions);
+		let settings = RunSettings {
+			n: config.n,
+			threads: config.threads,
+			spawn_n: config.spawn_n,
+			resume_file: None,
+			begin_file: None,
+			polycubes_file: None,
+			config: RunConfig {
+				n: Some(config.n),
+				threads: Some(config.threads),
+				spawn_n: Some(config.spawn_n),
+				..RunConfig::default()
+			},
+			halt_file: None,
+			verbose: false
+		};
+		for _ in 0..warmup {
+			run_count(&settings);
+		}
+		take_search_stats_total();
+		let mut wall_secs: Vec<f64> = Vec::with_capacity(repetitions);
+		let mut stats = SearchStats::new();
+		let mut counts = [0usize; 23];
+		for repetition in 0..repetitions {
+			let run_start = Instant::now();
+			run_count(&settings);
+			let wall_sec = run_start.elapsed().as_secs_f64();
+			stats = take_search_stats_total();
+			counts = unsafe { N_COUNTS };
+			println!("  run {}: {:.6} seconds", repetition + 1, wall_sec);
+			wall_secs.push(wall_sec);
+		}
+		let mean = wall_secs.iter().sum::<f64>() / wall_secs.len() as f64;
+		let min = wall_secs.iter().cloned().fold(f64::INFINITY, f64::min);
+		let max = wall_secs.iter().cloned().fold(0.0, f64::max);
+		println!("  wall seconds: mean={:.6} min={:.6} max={:.6}", mean, min, max);
+		println!("  n={} polycubes: {} ({:.0}/sec)", config.n, counts[config.n as usize], counts[config.n as usize] as f64 / mean);
+		println!("  canonical encodings computed: {}", stats.canonical_encodings);
+		#[allow(unused_mut)]
+		let mut result = serde_json::json!({
+			"n": config.n,
+			"threads": config.threads,
+			"spawn_n": config.spawn_n,
+			"wall_sec": wall_secs,
+			"wall_sec_mean": mean,
+			"wall_sec_min": min,
+			"wall_sec_max": max,
+			"per_n": (1..=config.n as usize).map(|i| serde_json::json!({
+				"n": i,
+				"count": counts[i],
+				"polycubes_per_sec": counts[i] as f64 / mean
+			})).collect::<Vec<serde_json::Value>>(),
+			"canonical_encodings": stats.canonical_encodings,
+			"invariant_checks": stats.invariant_checks,
+			"invariant_rejections": stats.invariant_rejections
+		});
+		#[cfg(feature = "instrument")]
+		{
+			result["instrument"] = stats.instrument.to_json();
+		}
+		results.push(result);
+	}
+	serde_json::json!({
+		"git_commit": git_commit(),
+		"cpu_model": cpu_model(),
+		"available_parallelism": thread::available_parallelism().map(|p| p.get()).unwrap_or(1),
+		"os": env::consts::OS,
+		"arch": env::consts::ARCH,
+		"timestamp": Utc::now().to_rfc3339(),
+		"canonicalizer": canonicalizer().name(),
+		"lane_backend": format!("{:?}", lane_backend()),
+		"warmup": warmup,
+		"repetitions": repetitions,
+		"results": results
+	})
 }
 
-// expand the polycube until we reach n=delegate_at_n (spawn_n) and
-//   and that point, place a .copy() of any found polycubes to
-//   enumerate into the submit queue
-pub fn extend_and_delegate(polycube: &Polycube, limit_n: u8, delegate_at_n: u8,
-	submit_queue: &Arc<ArrayQueue<Polycube>>, response_queue: &Arc<ArrayQueue<ThreadResponse>>,
-	atomic_halt: &Arc<AtomicBool>, rng: &mut ThreadRng) -> Option<[usize; 23]> {
+// exit codes, besides 0 for success
+// (clap also exits with EXIT_USAGE for arguments it can't parse)
+pub const EXIT_ERROR: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+pub const EXIT_INCOMPLETE: i32 = 3;
+pub const EXIT_CHECK_FAILED: i32 = 4;
 
-	let mut found_counts_by_n: [usize; 23] = [0; 23];
+const AFTER_HELP: &str = "\
+environment variables:
+  CUBES_THREADS, CUBES_SPAWN_N and CUBES_CANONICALIZER set the defaults for
+  --threads, --spawn-n and --canonicalizer when counting or resuming, which
+  the settings in a --config file or resume file take precedence over
 
-	// we are done if we've reached the desired n,
-	//   which we need to stop at because we are doing
-	//   a depth-first recursive evaluation
-	if polycube.n == limit_n {
-		return Some(found_counts_by_n);
-	}
+exit codes:
+  0  success
+  1  error
+  2  invalid arguments
+  3  the count was halted or didn't complete, so its results are partial
+  4  a check found problems";
 
-	// keep a Set of all evaluated positions so we don't repeat them
-	let mut tried_pos: BTreeSet<isize> = BTreeSet::new();
-	tried_pos.extend(polycube.cube_info_by_pos.keys());
+#[derive(Parser)]
+#[command(version, about = "Counts polycubes, and inspects, samples and ranks them", after_help = AFTER_HELP)]
+pub struct Cli {
+	#[command(subcommand)]
+	pub command: CliCommand
+}
 
-	let mut tried_canonicals: BTreeSet<u128> = BTreeSet::new();
+#[derive(Subcommand)]
+pub enum CliCommand {
+	#[command(about = "Count the polycubes of each size up to n", after_help = AFTER_HELP)]
+	Count(CountArgs),
+	#[command(about = "Resume a halted count from its resume file", after_help = AFTER_HELP)]
+	Resume(ResumeArgs),
+	#[command(about = "Write every polycube of size n to a polycubes file, for counting from with count --begin-from-file")]
+	GenerateJobs(GenerateJobsArgs),
+	#[command(about = "Split the jobs in a polycubes file or resume file into polycubes files, for counting separately")]
+	Split(SplitArgs),
+	#[command(about = "Merge polycubes files of the same size into one")]
+	Merge(MergeArgs),
+	#[command(about = "Convert the polycubes in a polycubes file or resume file to another format")]
+	Convert(ConvertArgs),
+	#[command(about = "Describe a single polycube")]
+	Inspect(InspectArgs),
+	#[command(about = "Print a polycube's parent (or all its ancestors) in the canonical extension tree",
+		after_help = "a polycube's parent is the polycube without its least significant cube")]
+	Parent(ParentArgs),
+	#[command(about = "Print a polycube's children in the canonical extension tree",
+		after_help = "the children are printed in increasing order of their canonical encodings,\n\
+		which is their order in the enumeration (see: rank --help)")]
+	Children(PolycubeArg),
+	#[command(about = "Print polycubes' sizes and ranks",
+		after_help = "the rank is the polycube's index among all the polycubes of its size, in the\n\
+		order of a depth-first walk of the canonical extension tree (see: unrank --help)\n\
+		the first rank of a size can take as long as enumerating the polycubes of that size,\n\
+		but the rest given at once are quicker, since counts near the root are kept")]
+	Rank(RankArgs),
+	#[command(about = "Print the polycube of size n with a rank",
+		after_help = "the polycube is printed as its canonical encoding in base94 (see: rank --help)\n\
+		several ranks can be given at once, like the first and last of a shard, which is\n\
+		quicker than one at a time")]
+	Unrank(UnrankArgs),
+	#[command(about = "Count a polycube's descendants in the canonical extension tree at each size up to n")]
+	Subtree(SubtreeArgs),
+	#[command(about = "Estimate the counts up to n with Knuth's random-path estimator")]
+	Estimate(EstimateArgs),
+	#[command(about = "Sample random polycubes of size n, printed as canonical encodings, one per line", after_help = AFTER_HELP)]
+	Sample(SampleArgs),
+	#[command(about = "Check a resume file's jobs and bookkeeping, as is done before resuming", after_help = AFTER_HELP)]
+	Check(CheckArgs),
+	#[command(about = "Check that all canonicalizers agree on random polycubes", after_help = AFTER_HELP)]
+	CheckCanonicalizers(CheckCanonicalizersArgs),
+	#[command(about = "Benchmark the standard single- and multi-threaded settings")]
+	Bench(BenchArgs)
+}
 
-	let mut canonical_try: &CanonicalInfo;
-	let mut canonical_try_clone: CanonicalInfo;
-	let mut least_significant_cube_pos: isize;
+#[derive(Args)]
+pub struct CanonicalizerArg {
+	#[arg(short, long, env = "CUBES_CANONICALIZER", value_parser = PossibleValuesParser::new(CANONICALIZERS.map(|c| c.name())),
+		help = format!("The encoding implementation to use [default: {}]", CANONICALIZERS[0].name()))]
+	pub canonicalizer: Option<String>
+}
 
-	let mut tmp_add = polycube.copy();
-	let canonical_orig_enc: u128 = tmp_add.find_canonical_info(IMPOSSIBLE_POS).enc;
-	let canonical_orig_enc_shifted = canonical_orig_enc << 6;
+impl CanonicalizerArg {
+	pub fn select(&self) {
+		if let Some(name) = self.canonicalizer.as_ref() {
+			// (the name was checked when parsing)
+			select_canonicalizer(name).unwrap();
+		}
+	}
+}
 
-	let mut try_pos: isize;
+#[derive(Args)]
+pub struct CountArgs {
+	#[arg(short = 'n', long = "n", value_parser = value_parser!(u8).range(2..=21), required_unless_present = "config",
+		help = "The number of cubes the largest counted polycube should contain")]
+	pub n: Option<u8>,
+	#[arg(short, long, env = "CUBES_THREADS", value_parser = parse_count_threads,
+		help = "0 for single-threaded, or >1 for the maximum number of threads to spawn simultaneously [default: 0]")]
+	pub threads: Option<u8>,
+	#[arg(short, long, env = "CUBES_SPAWN_N", value_parser = value_parser!(u8).range(4..),
+		help = "The polycube size to give to worker threads, where higher->more shorter-lived threads (8<=recommended<=12)")]
+	pub spawn_n: Option<u8>,
+	#[arg(short, long, value_name = "BEGIN_FILE", value_parser = |s: &str| validate_resume_file_arg(s, "<begin-file>"),
+		help = "A polycubes .txt.gz file, whose polycubes are counted from instead of from the single cube")]
+	pub begin_from_file: Option<PathBuf>,
+	#[arg(short, long, value_name = "POLYCUBES_FILE", value_parser = |s: &str| validate_polycubes_file_arg(s, "<polycubes-file>").map(|_| s.to_string()),
+		help = "A .txt file to append the polycubes of size n to")]
+	pub write_found_polycubes_file: Option<String>,
+	#[arg(long, value_parser = validate_work_dir_arg,
+		help = "The directory to keep the halt file, lock file, and resume and quarantine files in [default: the executable's directory]")]
+	pub work_dir: Option<PathBuf>,
+	#[arg(long, value_name = "MINUTES", value_parser = value_parser!(u32).range(1..),
+		help = "Write a resume file this often and carry on from it, replacing the one written before [default: only when halted]")]
+	pub checkpoint_minutes: Option<u32>,
+	#[command(flatten)]
+	pub canonicalizer: CanonicalizerArg,
+	#[arg(long, help = "A .toml file of settings, named like these flags (for example: n = 12, spawn_n = 8), which the flags override")]
+	pub config: Option<PathBuf>,
+	#[arg(long, value_enum, help = "How to write progress and results to stdout [default: text]")]
+	pub output_format: Option<OutputFormat>,
+	#[arg(long, conflicts_with = "output_format", help = "Write newline-delimited json events to stdout instead of the human-readable output (the same as --output-format json)")]
+	pub json: bool,
+	#[arg(long, help = "If the polycubes file ends with an interrupted write, drop it and append after the last complete batch")]
+	pub truncate_polycubes_file: bool
+}
 
-	// if halt has been signalled, abandon the evaluation
-	//   of this polycube
-	// since this function is run many many times by each process/thread,
-	//   we can greatly reduce use of AtomicBool.load() and increase per-
-	//   process CPU utilization
-	if rng.gen_range(0..1000) == 0 && atomic_halt.load(Ordering::Relaxed) {
-		return None;
-	}
+#[derive(Args)]
+pub struct ResumeArgs {
+	#[arg(value_parser = |s: &str| validate_resume_file_arg(s, "<resume-file>"),
+		help = "A resume .txt.gz file written by a halted count")]
+	pub resume_file: PathBuf,
+	#[arg(short, long, env = "CUBES_THREADS", value_parser = parse_count_threads,
+		help = "The maximum number of threads to spawn simultaneously (>1) [default: the halted run's]")]
+	pub threads: Option<u8>,
+	#[arg(short, long, value_name = "POLYCUBES_FILE", value_parser = |s: &str| validate_polycubes_file_arg(s, "<polycubes-file>").map(|_| s.to_string()),
+		help = "A .txt file to append the polycubes of size n to [default: the halted run's]")]
+	pub write_found_polycubes_file: Option<String>,
+	#[arg(long, value_parser = validate_work_dir_arg,
+		help = "The directory to keep the halt file, lock file, and resume and quarantine files in [default: the halted run's]")]
+	pub work_dir: Option<PathBuf>,
+	#[arg(long, value_name = "MINUTES", value_parser = value_parser!(u32).range(1..),
+		help = "Write a resume file this often and carry on from it, replacing the one written before [default: the halted run's]")]
+	pub checkpoint_minutes: Option<u32>,
+	#[command(flatten)]
+	pub canonicalizer: CanonicalizerArg,
+	#[arg(long, value_enum, help = "How to write progress and results to stdout [default: the halted run's]")]
+	pub output_format: Option<OutputFormat>,
+	#[arg(long, conflicts_with = "output_format", help = "Write newline-delimited json events to stdout instead of the human-readable output (the same as --output-format json)")]
+	pub json: bool,
+	#[arg(long, help = "If the polycubes file ends with an interrupted write, drop it and append after the last complete batch")]
+	pub truncate_polycubes_file: bool
+}
 
-	// for each cube, for each direction, add a cube
-	for cube_pos in polycube.cube_info_by_pos.keys() {
-		for direction_cost in DIRECTION_COSTS {
-			try_pos = cube_pos + direction_cost;
+#[derive(Args)]
+pub struct GenerateJobsArgs {
+	#[arg(short = 'n', long = "n", value_parser = value_parser!(u8).range(1..=21),
+		help = "The size of the polycubes to write")]
+	pub n: u8,
+	#[arg(short, long, value_parser = parse_new_polycubes_file,
+		help = "The polycubes .txt file to write, which must not already exist")]
+	pub output: PathBuf
+}
 
-			// skip if we've already tried this position
-			if !tried_pos.insert(try_pos) {
-				continue;
-			}
+#[derive(Args)]
+pub struct SplitArgs {
+	#[arg(value_parser = |s: &str| validate_resume_file_arg(s, "<file>"),
+		help = "A polycubes .txt.gz file, or a resume .txt.gz file with --resume")]
+	pub file: PathBuf,
+	#[arg(short, long, value_parser = RangedU64ValueParser::<usize>::new().range(2..),
+		help = "The number of polycubes files to split the jobs into, named <file>-part<i>of<parts>.txt")]
+	pub parts: usize,
+	#[arg(long, help = "The file is a resume file, whose remaining jobs are split")]
+	pub resume: bool
+}
 
-			// create P+A
-			tmp_add.add(try_pos);
+#[derive(Args)]
+pub struct MergeArgs {
+	#[arg(required = true, value_parser = |s: &str| validate_resume_file_arg(s, "<file>"),
+		help = "Polycubes .txt.gz files, all of the same size of polycube")]
+	pub files: Vec<PathBuf>,
+	#[arg(short, long, value_parser = parse_new_polycubes_file,
+		help = "The polycubes .txt file to write, which must not already exist")]
+	pub output: PathBuf
+}
 
-			// skip if we've already seen some P+A with the same canonical representation
-			//   (comparing the bitwise int only)
-			canonical_try = tmp_add.find_canonical_info_with_target(try_pos, canonical_orig_enc_shifted);
-			if !tried_canonicals.insert(canonical_try.enc) {
-				tmp_add.remove(try_pos);
-				continue;
-			}
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ConvertFormat {
+	// a polycubes file, with base94 encodings
+	Polycubes,
+	// one decimal encoding per line
+	Decimal,
+	// one polycube's coordinates per line, as for inspect --coordinates
+	Coordinates
+}
 
-			least_significant_cube_pos = canonical_try.least_significant_cube_pos;
+#[derive(Args)]
+pub struct ConvertArgs {
+	#[arg(value_parser = |s: &str| validate_resume_file_arg(s, "<file>"),
+		help = "A polycubes .txt.gz file, or a resume .txt.gz file with --resume")]
+	pub file: PathBuf,
+	#[arg(long, help = "The file is a resume file, whose remaining jobs are converted")]
+	pub resume: bool,
+	#[arg(long, conflicts_with = "resume",
+		help = "The file is a polycubes file written before batches were closed with trailers, which is read to its end (so that it can be converted to a polycubes file with trailers)")]
+	pub legacy: bool,
+	#[arg(long, value_enum, help = "The format to convert to")]
+	pub to: ConvertFormat,
+	#[arg(short, long, help = "The file to write, which must not already exist (and must be a .txt file for a polycubes file)")]
+	pub output: PathBuf
+}
 
-			// if try_pos (cube A) is the least significant, then P+A-A==P and P+A is a new unique polycube
-			if least_significant_cube_pos == try_pos {
-				found_counts_by_n[tmp_add.n as usize] += 1;
-				// the initial delegator submits jobs for threads,
-				//   but only if the found polycube has n=spawn_n
-				if tmp_add.n == delegate_at_n {
-					match submit_queue.push(tmp_add.copy()) {
-						Ok(_) => {}
-						Err(_) => {
-							panic!("submit_queue.push() failed");
-						}
-					}
-				} else {
-					match extend_and_delegate(&mut tmp_add.copy(),
-							limit_n, delegate_at_n,
-							submit_queue, response_queue, atomic_halt, rng) {
-						Some(futher_counts) => {
-							for i in 1..limit_n+1 {
-								found_counts_by_n[i as usize] += futher_counts[i as usize];
-							}
-						}
-						// if we have detected a halt while running the recursion,
-						//   we can continue to bubble the halt back up
-						None => {
-							return None;
-						}
-					}
-				}
-			} else {
-				canonical_try_clone = canonical_try.clone();
-				// remove the last of the ordered cubes (cube B) in P+A
-				tmp_add.remove(least_significant_cube_pos);
-				// if P+A-B has the same canonical representation as P, count P+A as a new unique polycube
-				//   and continue recursion into that P+A
-				if tmp_add.find_canonical_enc_with_target(canonical_orig_enc) == canonical_orig_enc {
-					// replace the least significant cube we just removed
-					tmp_add.add(least_significant_cube_pos);
-					// replace the canonical info from before
-					tmp_add.canonical_info = Some(canonical_try_clone);
-					found_counts_by_n[tmp_add.n as usize] += 1;
-					// the initial delegator submits jobs for threads,
-					//   but only if the found polycube has n=spawn_n
-					if tmp_add.n == delegate_at_n {
-						match submit_queue.push(tmp_add.copy()) {
-							Ok(_) => {}
-							Err(_) => {
-								panic!("submit_queue.push() failed");
-							}
-						}
-					} else {
-						match extend_and_delegate(&mut tmp_add.copy(),
-								limit_n, delegate_at_n,
-								submit_queue, response_queue, atomic_halt, rng) {
-							Some(futher_counts) => {
-								for i in 1..limit_n+1 {
-									found_counts_by_n[i as usize] += futher_counts[i as usize];
-								}
-							}
-							// if we have detected a halt while running the recursion,
-							//   we can continue to bubble the halt back up
-							None => {
-								return None;
-							}
-						}
-					}
+#[derive(Args)]
+#[command(group(ArgGroup::new("input").required(true).args(["polycube", "coordinates"])))]
+pub struct InspectArgs {
+	#[arg(allow_hyphen_values = true, value_parser = parse_polycube_arg,
+		help = "A polycube's canonical encoding in base94, as written to polycubes files")]
+	pub polycube: Option<String>,
+	#[arg(long, help = "The cubes' coordinates, as x,y,z separated by spaces or semicolons (for example: \"0,0,0 1,0,0 1,1,0\")")]
+	pub coordinates: Option<String>
+}
 
-				// undo the temporary removal of the least significant cube,
-				//   but only if it's not the same as the cube we just tried
-				//   since we remove that one before going to the next iteration
-				//   of the loop
-				} else {
-					tmp_add.add(least_significant_cube_pos);
-				}
-			}
+#[derive(Args)]
+pub struct PolycubeArg {
+	#[arg(allow_hyphen_values = true, value_parser = parse_polycube_arg,
+		help = "A polycube's canonical encoding in base94, as written to polycubes files")]
+	pub polycube: String
+}
 
-			// revert creating P+A to try adding a cube at another position
-			tmp_add.remove(try_pos);
-		}
-	}
-	return Some(found_counts_by_n);
+#[derive(Args)]
+pub struct ParentArgs {
+	#[command(flatten)]
+	pub polycube: PolycubeArg,
+	#[arg(long, help = "Print all of the polycube's ancestors back to the monocube, with their sizes")]
+	pub ancestry: bool
 }
 
-// same as extend_single_thread, but
-//   - we report counts to the results queue
-//   - we occasionally check for a halt signal
-pub fn extend_as_worker(polycube: &mut Polycube, limit_n: u8,
-		submit_queue: &Arc<ArrayQueue<Polycube>>,
-		atomic_halt: &Arc<AtomicBool>,
-		rng: &mut ThreadRng,
-		save_final_polycubes: bool,
-		final_polycube_encs: &mut Vec<u128>) -> Option<[usize; 23]> {
+#[derive(Args)]
+pub struct UnrankArgs {
+	#[arg(short = 'n', long = "n", value_parser = value_parser!(u8).range(1..=21),
+		help = "The size of the polycube")]
+	pub n: u8,
+	#[arg(short, long, required = true, help = "The polycube's index among all the polycubes of size n, from 0 (can be repeated)")]
+	pub rank: Vec<u128>
+}
 
-	let mut found_counts_by_n: [usize; 23] = [0; 23];
+#[derive(Args)]
+pub struct RankArgs {
+	#[arg(required = true, allow_hyphen_values = true, value_parser = parse_polycube_arg,
+		help = "Polycubes' canonical encodings in base94, as written to polycubes files")]
+	pub polycubes: Vec<String>
+}
 
-	//found_counts_by_n[polycube.n as usize] += 1;
+#[derive(Args)]
+pub struct SubtreeArgs {
+	#[arg(long, allow_hyphen_values = true, value_parser = parse_polycube_arg,
+		help = "A polycube's canonical encoding in base94, as written to polycubes files")]
+	pub seed: String,
+	#[arg(short = 'n', long = "n", value_parser = value_parser!(u8).range(2..=21),
+		help = "The number of cubes the largest counted polycube should contain")]
+	pub n: u8,
+	#[arg(short, long, default_value_t = 0,
+		help = "0 for single-threaded, or >0 for the number of threads to count on")]
+	pub threads: usize,
+	#[arg(short, long, help = "The polycube size the subtree is split at for the threads [default: 2 more than the seed's]")]
+	pub spawn_n: Option<u8>
+}
 
-	// we are done if we've reached the desired n,
-	//   which we need to stop at because we are doing
-	//   a depth-first recursive evaluation
-	if polycube.n == limit_n {
-		if save_final_polycubes {
-			final_polycube_encs.push(polycube.find_canonical_info(IMPOSSIBLE_POS).enc);
-		}
-		return Some(found_counts_by_n);
-	}
+#[derive(Args)]
+pub struct EstimateArgs {
+	#[arg(short = 'n', long = "n", value_parser = value_parser!(u8).range(2..=21),
+		help = "The largest polycube size to estimate the count of")]
+	pub n: u8,
+	#[arg(long, default_value_t = 1000, value_parser = RangedU64ValueParser::<usize>::new().range(2..),
+		help = "The number of random walks to take")]
+	pub samples: usize,
+	#[arg(short, long, default_value_t = 1, value_parser = RangedU64ValueParser::<usize>::new().range(1..),
+		help = "The number of threads to take the walks on")]
+	pub threads: usize,
+	#[command(flatten)]
+	pub canonicalizer: CanonicalizerArg
+}
 
-	// keep a Set of all evaluated positions so we don't repeat them
-	let mut tried_pos: BTreeSet<isize> = BTreeSet::new();
+#[derive(Args)]
+pub struct SampleArgs {
+	#[arg(short = 'n', long = "n", value_parser = value_parser!(u8).range(2..=21),
+		help = "The size of the polycubes to sample")]
+	pub n: u8,
+	#[arg(long, default_value_t = 1, value_parser = RangedU64ValueParser::<usize>::new().range(1..),
+		help = "The number of polycubes to sample")]
+	pub count: usize,
+	#[arg(long, help = "The seed for the random number generator [default: random, printed to stderr]")]
+	pub seed: Option<u64>,
+	#[arg(long, default_value_t = SAMPLE_WALKS, value_parser = RangedU64ValueParser::<usize>::new().range(1..),
+		help = "The random walks weighed for each polycube, where more make the samples closer to uniform but take longer")]
+	pub walks: usize,
+	#[arg(short, long, default_value_t = 1, value_parser = RangedU64ValueParser::<usize>::new().range(1..),
+		help = "The number of threads to sample on")]
+	pub threads: usize,
+	#[arg(long, help = "Instead of printing the samples, enumerate all the polycubes of size n (<=8) and test whether they were drawn uniformly")]
+	pub check_uniformity: bool
+}
 
-	let mut tried_canonicals: BTreeSet<u128> = BTreeSet::new();
+#[derive(Args)]
+pub struct CheckArgs {
+	#[arg(value_parser = |s: &str| validate_resume_file_arg(s, "<resume-file>"),
+		help = "A resume .txt.gz file, whose jobs are all rebuilt and checked")]
+	pub resume_file: PathBuf
+}
 
-	let canonical_orig_enc: u128 = polycube.find_canonical_info(IMPOSSIBLE_POS).enc;
-	let canonical_orig_enc_shifted = canonical_orig_enc << 6;
-	let mut canonical_try: &CanonicalInfo;
-	let mut canonical_try_clone: CanonicalInfo;
-	let mut least_significant_cube_pos: isize;
+#[derive(Args)]
+pub struct CheckCanonicalizersArgs {
+	#[arg(help = "The number of random polycubes to check")]
+	pub count: usize,
+	#[arg(long, value_parser = RangedU64ValueParser::<u8>::new().range(1..=13),
+		help = "Also count all the polycubes up to this n (at most 13) with each canonicalizer, and check the counts against the well-known ones")]
+	pub counts: Option<u8>
+}
 
-	let mut try_pos: isize;
+#[derive(Args)]
+pub struct BenchArgs {
+	#[arg(long, default_value_t = 3, value_parser = RangedU64ValueParser::<usize>::new().range(1..),
+		help = "The number of timed runs of each configuration")]
+	pub repetitions: usize,
+	#[arg(long, default_value_t = 1, help = "The number of untimed runs of each configuration before timing")]
+	pub warmup: usize,
+	#[arg(long, help = "Run a smaller matrix (n<=10) for quick comparisons")]
+	pub quick: bool,
+	#[command(flatten)]
+	pub canonicalizer: CanonicalizerArg,
+	#[arg(short, long, help = "Where to write the json results [default: bench-<timestamp>.json beside the executable]")]
+	pub output: Option<PathBuf>
+}
 
-	// if halt has been signalled, abandon the evaluation
-	//   of this polycube
-	// since this function is run many many times by each process/thread,
-	//   we can greatly reduce use of AtomicBool.load() and increase per-
-	//   process CPU utilization
-	if rng.gen_range(0..1000) == 0 && atomic_halt.load(Ordering::Relaxed) {
-		return None;
+pub fn parse_count_threads(s: &str) -> Result<u8, String> {
+	match s.parse::<u8>() {
+		Ok(1) => Err(String::from("must be 0 or greater than 1")),
+		Ok(threads) => Ok(threads),
+		Err(err) => Err(err.to_string())
 	}
+}
 
-	// for each cube, for each direction, add a cube
-	// create a list to iterate over because the dict will change
-	//   during recursion within the loop
-	let original_positions: Vec<isize> = polycube.cube_info_by_pos.keys().cloned().collect();
-	// include all existing cubes' positions in the tried_pos set
-	tried_pos.extend(original_positions.iter());
-	for cube_pos in original_positions {
-		for direction_cost in DIRECTION_COSTS {
-			try_pos = cube_pos + direction_cost;
+pub fn parse_polycube_arg(s: &str) -> Result<String, String> {
+	polycube_from_encoding_str(s).map(|_| s.to_string())
+}
 
-			// skip if we've already tried this position
-			if !tried_pos.insert(try_pos) {
-				continue;
-			}
+// a polycubes file to write from scratch, since an existing one
+//   would be appended to
+pub fn parse_new_polycubes_file(s: &str) -> Result<PathBuf, String> {
+	let path = validate_polycubes_file_arg(s, "<output>")?;
+	if path.exists() {
+		return Err(format!("[{}] already exists", s));
+	}
+	Ok(path)
+}
 
-			// create P+A
-			polycube.add(try_pos);
+// exit with a usage error for a subcommand, formatted the way clap
+//   formats the errors it finds
+pub fn usage_error(subcommand: &str, message: String) -> ! {
+	let mut command = Cli::command();
+	command.build();
+	let subcommand = command.find_subcommand_mut(subcommand).unwrap();
+	subcommand.error(ErrorKind::ArgumentConflict, message).exit()
+}
 
-			// skip if we've already seen some p+1 with the same canonical representation
-			//   (comparing the bitwise int only)
-			canonical_try = polycube.find_canonical_info_with_target(try_pos, canonical_orig_enc_shifted);
-			if !tried_canonicals.insert(canonical_try.enc) {
-				polycube.remove(try_pos);
-				continue;
-			}
+// the arguments from before there were subcommands (for example
+//   "--threads 7 --spawn-n 7 -n 11") still work: they're given to
+//   the count command, or to resume or check-canonicalizers if they
+//   have a --resume-from-file or --check-canonicalizers argument
+pub fn with_legacy_subcommand(mut args: Vec<String>) -> Vec<String> {
+	match args.get(1) {
+		Some(first) if first.starts_with('-') && !["-h", "--help", "-V", "--version"].contains(&first.as_str()) => {}
+		_ => return args
+	}
+	for (flags, subcommand) in [(&["--resume-from-file", "-r"][..], "resume"), (&["--check-canonicalizers"][..], "check-canonicalizers")] {
+		if let Some(i) = args.iter().position(|arg| flags.contains(&arg.as_str())) {
+			// "--resume-from-file <file>" becomes "resume <file>"
+			args.remove(i);
+			let value = if i < args.len() { args.remove(i) } else { String::new() };
+			args.splice(1..1, [subcommand.to_string(), value]);
+			return args;
+		}
+	}
+	args.insert(1, String::from("count"));
+	args
+}
 
-			least_significant_cube_pos = canonical_try.least_significant_cube_pos;
+// run a count (or a resumed count) until it completes or is halted,
+//   printing the results, then exit
+// resume files are written uncompressed and only read once gzipped, so
+//   a checkpoint's is gzipped (in place of the uncompressed one) to be
+//   carried on from
+pub fn gzip_checkpoint(resume_file: &Path) -> PathBuf {
+	let gz_path = PathBuf::from(format!("{}.gz", resume_file.to_string_lossy()));
+	write_file_atomically(&gz_path, "checkpoint", |file_buf| {
+		let mut gz = flate2::write::GzEncoder::new(file_buf, flate2::Compression::default());
+		std::io::copy(&mut File::open(resume_file)?, &mut gz)?;
+		gz.finish()?;
+		Ok(())
+	});
+	if let Err(err) = std::fs::remove_file(resume_file) {
+		report_warning(format!("failed to remove the uncompressed checkpoint [{}]: {}", resume_file.to_string_lossy(), err));
+	}
+	gz_path
+}
 
-			// if try_pos (cube A) is the least significant, then P+A-A==P and P+A is a new unique polycube
-			if least_significant_cube_pos == try_pos {
-				found_counts_by_n[polycube.n as usize] += 1;
-				match extend_as_worker(polycube, limit_n,
-						submit_queue, atomic_halt, rng,
-						save_final_polycubes, final_polycube_encs) {
-					Some(futher_counts) => {
-						for i in 1..limit_n+1 {
-							found_counts_by_n[i as usize] += futher_counts[i as usize];
-						}
-					}
-					// if we have detected a halt while running the recursion,
-					//   we can continue to bubble the halt back up
-					None => {
-						return None;
-					}
-				}
-			} else {
-				canonical_try_clone = canonical_try.clone();
-				// remove the last of the ordered cubes (cube B) in P+A
-				polycube.remove(least_significant_cube_pos);
-				// if P+A-B has the same canonical representation as P, count P+A as a new unique polycube
-				//   and continue recursion into that P+A
-				if polycube.find_canonical_enc_with_target(canonical_orig_enc) == canonical_orig_enc {
-					// replace the least significant cube we just removed
-					polycube.add(least_significant_cube_pos);
-					// replace the canonical info from before
-					polycube.canonical_info = Some(canonical_try_clone);
-					found_counts_by_n[polycube.n as usize] += 1;
-					// continue recursion
-					match extend_as_worker(polycube, limit_n,
-							submit_queue, atomic_halt, rng, save_final_polycubes, final_polycube_encs) {
-						Some(futher_counts) => {
-							for i in 1..limit_n+1 {
-								found_counts_by_n[i as usize] += futher_counts[i as usize];
-							}
-						}
-						// if we have detected a halt while running the recursion,
-						//   we can continue to bubble the halt back up
-						None => {
-							return None;
-						}
-					}
+pub fn run_main(settings: RunSettings, truncate_polycubes_file: bool) -> ! {
+	let halt_file_path = create_work_file(settings.config.work_dir.as_deref(), "halt-signal.txt");
+	if halt_file_path.exists() {
+		if json_events() {
+			report_halt(&halt_file_path);
+		} else {
+			println!("found halt file [{}] already exists, stopping...", halt_file_path.to_str().unwrap());
+		}
+		exit(EXIT_INCOMPLETE);
+	}
+
+	// runs with the same work directory share the halt file, so only one
+	//   can run at a time, and only one run can write to a polycubes file
+	let run_lock = match RunLock::acquire(create_work_file(settings.config.work_dir.as_deref(), "run.lock"), "the work directory") {
+		Ok(lock) => lock,
+		Err(reason) => {
+			report_error(reason);
+			exit(EXIT_ERROR);
+		}
+	};
+	let polycubes_file_lock = match settings.polycubes_file.as_ref() {
+		Some(polycubes_file_path) => match RunLock::acquire(PathBuf::from(format!("{}.lock", polycubes_file_path)), "the polycubes file") {
+			Ok(lock) => Some(lock),
+			Err(reason) => {
+				report_error(reason);
+				// exit() doesn't run destructors
+				drop(run_lock);
+				exit(EXIT_ERROR);
+			}
+		},
+		None => None
+	};
+	if let Some(polycubes_file_path) = settings.polycubes_file.as_ref() {
+		if let Err(err) = prepare_polycubes_file(polycubes_file_path, settings.n, truncate_polycubes_file) {
+			report_error(err);
+			drop(polycubes_file_lock);
+			drop(run_lock);
+			exit(EXIT_ERROR);
+		}
+	}
 
-				// undo the temporary removal of the least significant cube,
-				//   but only if it's not the same as the cube we just tried
-				//   since we remove that one before going to the next iteration
-				//   of the loop
-				} else {
-					polycube.add(least_significant_cube_pos);
+	if json_events() {
+		emit_event("start", serde_json::json!({
+			"config": serde_json::to_value(&settings.config).unwrap(),
+			"resume_file": settings.resume_file.as_ref().map(|path| path.to_string_lossy()),
+			"halt_file": halt_file_path.to_string_lossy()
+		}));
+	} else {
+		println!("config: {}", settings.config.to_header_value());
+	}
+	let mut settings = RunSettings {
+		halt_file: Some(halt_file_path),
+		..settings
+	};
+	// after each checkpoint, the run carries on from the resume file it
+	//   wrote, which replaces the checkpoint's before it once the next
+	//   resume file is written or the run completes (a resume file the
+	//   run started from is left alone)
+	let mut checkpoint_file: Option<PathBuf> = None;
+	let outcome = loop {
+		let outcome = run_count(&settings);
+		if outcome.complete || outcome.resume_file.is_some() {
+			if let Some(checkpoint_file) = checkpoint_file.take() {
+				if let Err(err) = std::fs::remove_file(&checkpoint_file) {
+					report_warning(format!("failed to remove the earlier checkpoint [{}]: {}", checkpoint_file.to_string_lossy(), err));
 				}
 			}
-
-			// revert creating P+A to try adding a cube at another position
-			polycube.remove(try_pos);
 		}
+		match outcome.resume_file.as_ref().filter(|_| outcome.checkpoint && !outcome.complete) {
+			Some(resume_file) => {
+				let resume_file = gzip_checkpoint(resume_file);
+				if !json_events() {
+					println!("carrying on from the checkpoint [{}]", resume_file.to_string_lossy());
+				}
+				checkpoint_file = Some(resume_file.clone());
+				settings.resume_file = Some(resume_file);
+				settings.begin_file = None;
+			}
+			None => break outcome
+		}
+	};
+	if json_events() {
+		emit_event("results", serde_json::json!({
+			"n": outcome.n,
+			"complete": outcome.complete,
+			"counts": counts_to_json(outcome.n, Some(&outcome.count_status)),
+			"projected_count": projected_count_to_json(outcome.projected_count),
+			"elapsed_sec": outcome.elapsed_sec,
+			"total_elapsed_sec": outcome.elapsed_sec + outcome.previous_total_elapsed_sec,
+			"stats": SEARCH_STATS_TOTAL.lock().unwrap().to_json()
+		}));
+		drop(polycubes_file_lock);
+		drop(run_lock);
+		exit(if outcome.complete { 0 } else { EXIT_INCOMPLETE });
 	}
-	return Some(found_counts_by_n);
+	print_results(outcome.n, &outcome.count_status);
+	if let Some(projected_count) = outcome.projected_count {
+		println!("projected final count for n = {}: {}", outcome.n, projected_count.describe());
+	}
+	SEARCH_STATS_TOTAL.lock().unwrap().print();
+	if outcome.previous_total_elapsed_sec == 0.0 {
+		println!("elapsed seconds: {:.6}", outcome.elapsed_sec);
+	} else {
+		let total_time_elapsed = outcome.elapsed_sec + outcome.previous_total_elapsed_sec;
+		println!("elapsed seconds: {:.6} + {:.6} (previously) = {:.6}", outcome.elapsed_sec, outcome.previous_total_elapsed_sec, total_time_elapsed);
+	}
+	drop(polycubes_file_lock);
+	drop(run_lock);
+	exit(if outcome.complete { 0 } else { EXIT_INCOMPLETE });
 }
 
-pub fn extend_single_thread(polycube: &mut Polycube, limit_n: u8, depth: usize) {
-	// since this is a valid polycube, increment the count
-	unsafe {
-		N_COUNTS[polycube.n as usize] += 1;
+// handle the "count" command, where the flags (and environment
+//   variables) override the settings in the --config file, if any
+pub fn count_main(args: CountArgs, matches: &ArgMatches) -> ! {
+	let file_config = match args.config.as_ref() {
+		Some(config_path) => match RunConfig::from_file(config_path) {
+			Ok(config) => config,
+			Err(err) => usage_error("count", err)
+		},
+		None => RunConfig::default()
+	};
+	let (mut env_config, flag_config) = RunConfig {
+		n: args.n,
+		threads: args.threads,
+		spawn_n: args.spawn_n,
+		begin_from_file: args.begin_from_file,
+		write_found_polycubes_file: args.write_found_polycubes_file,
+		work_dir: args.work_dir,
+		checkpoint_minutes: args.checkpoint_minutes,
+		canonicalizer: args.canonicalizer.canonicalizer,
+		output_format: args.output_format.or(args.json.then_some(OutputFormat::Json))
+	}.split_env_settings(matches);
+	// (a spawn_n from the environment doesn't apply to a begin file)
+	if file_config.begin_from_file.is_some() || flag_config.begin_from_file.is_some() {
+		env_config.spawn_n = None;
 	}
-
-	// we are done if we've reached the desired n,
-	//   which we need to stop at because we are doing
-	//   a depth-first recursive evaluation
-	if polycube.n == limit_n {
-		return;
+	let mut config = env_config.overridden_by(file_config).overridden_by(flag_config);
+	if let Err(err) = config.validate() {
+		usage_error("count", err);
 	}
+	JSON_EVENTS.store(config.json_output(), Ordering::Relaxed);
+	// the defaults are filled in so that a resumed run uses the same
+	//   settings even if the defaults change
+	let n = config.n.unwrap();
+	let threads = *config.threads.get_or_insert(0);
+	select_canonicalizer(config.canonicalizer.get_or_insert(canonicalizer().name().to_string())).unwrap();
+	let spawn_n = config.spawn_n.unwrap_or(0);
+	if spawn_n > 0 && spawn_n >= n {
+		usage_error("count", String::from("<spawn-n> must be less than <n>"));
+	}
+	// (the single-threaded count always starts from the single cube)
+	if threads == 0 && config.begin_from_file.is_some() {
+		usage_error("count", String::from("<threads> must be greater than 1 to count from a <begin-file>"));
+	}
+	if threads > 0 && spawn_n < 1 && config.begin_from_file.is_none() {
+		usage_error("count", String::from("<spawn-n> must be given when <threads> is greater than 0"));
+	}
+	// (nor is it ever halted, so it has nothing to checkpoint)
+	if threads == 0 && config.checkpoint_minutes.is_some() {
+		usage_error("count", String::from("<threads> must be greater than 1 to write checkpoints"));
+	}
+	run_main(RunSettings {
+		n,
+		threads,
+		spawn_n,
+		resume_file: None,
+		begin_file: config.begin_from_file.clone(),
+		polycubes_file: config.write_found_polycubes_file.clone(),
+		halt_file: None,
+		verbose: !config.json_output(),
+		config
+	}, args.truncate_polycubes_file)
+}
 
-	// keep a Set of all evaluated positions so we don't repeat them
-	let mut tried_pos: BTreeSet<isize> = BTreeSet::new();
+// handle the "resume" command, which carries on with the halted
+//   run's settings, except for any given by the flags
+pub fn resume_main(args: ResumeArgs, matches: &ArgMatches) -> ! {
+	// errors reading the resume file's header are already json events
+	//   with --json, before the rest of the config is known
+	JSON_EVENTS.store(args.json || args.output_format == Some(OutputFormat::Json), Ordering::Relaxed);
+	let header = JobFileReader::open_header(&args.resume_file, JobFileFormat::Resume);
+	// (older resume files only have n and spawn_n)
+	let resume_config = header.config.unwrap_or(RunConfig {
+		n: Some(header.n),
+		spawn_n: Some(header.spawn_n),
+		..RunConfig::default()
+	});
+	let (env_config, flag_config) = RunConfig {
+		threads: args.threads,
+		write_found_polycubes_file: args.write_found_polycubes_file,
+		work_dir: args.work_dir,
+		checkpoint_minutes: args.checkpoint_minutes,
+		canonicalizer: args.canonicalizer.canonicalizer,
+		output_format: args.output_format.or(args.json.then_some(OutputFormat::Json)),
+		..RunConfig::default()
+	}.split_env_settings(matches);
+	let config = env_config.overridden_by(resume_config).overridden_by(flag_config);
+	JSON_EVENTS.store(config.json_output(), Ordering::Relaxed);
+	// (the single-threaded count always starts from the single cube)
+	let threads = config.threads.unwrap_or(0);
+	if threads == 0 {
+		usage_error("resume", String::from("<threads> must be greater than 1 to resume"));
+	}
+	if let Some(name) = config.canonicalizer.as_ref() {
+		if let Err(err) = select_canonicalizer(name) {
+			usage_error("resume", err);
+		}
+	}
+	if !json_events() {
+		println!("resuming from file: {}", args.resume_file.to_string_lossy());
+	}
+	run_main(RunSettings {
+		// (run_count reads n from the resume file too)
+		n: header.n,
+		threads,
+		spawn_n: 0,
+		resume_file: Some(args.resume_file),
+		begin_file: None,
+		polycubes_file: config.write_found_polycubes_file.clone(),
+		halt_file: None,
+		verbose: !config.json_output(),
+		config
+	}, args.truncate_polycubes_file)
+}
 
-	let mut tried_canonicals: BTreeSet<u128> = BTreeSet::new();
+// the jobs written to a polycubes file at once, when writing many
+const POLYCUBES_FILE_BATCH: usize = 1_000_000;
 
-	let canonical_orig_enc: u128 = polycube.find_canonical_info(IMPOSSIBLE_POS).enc;
-	let canonical_orig_enc_shifted = canonical_orig_enc << 6;
-	let mut canonical_try: &CanonicalInfo;
-	let mut canonical_try_clone: CanonicalInfo;
-	let mut least_significant_cube_pos: isize;
+// handle the "generate-jobs" command
+pub fn generate_jobs_main(args: GenerateJobsArgs) {
+	let output = args.output.to_string_lossy().to_string();
+	let mut batch: Vec<u128> = Vec::new();
+	let mut written: usize = 0;
+	for_each_polycube(args.n, &Arc::new(AtomicBool::new(false)), |polycube_enc| {
+		batch.push(polycube_enc);
+		if batch.len() >= POLYCUBES_FILE_BATCH {
+			write_polycubes_file(args.n, &output, &batch);
+			written += batch.len();
+			batch.clear();
+		}
+	});
+	write_polycubes_file(args.n, &output, &batch);
+	written += batch.len();
+	println!("wrote {} polycubes of size {} to [{}]", written, args.n, output);
+}
 
-	let mut try_pos: isize;
+// open a polycubes file or resume file for reading its polycubes,
+//   along with their size (for a resume file, the size of its jobs)
+pub fn open_job_file(path: &PathBuf, format: JobFileFormat) -> (JobFileReader, u8) {
+	if format != JobFileFormat::Resume {
+		let reader = JobFileReader::open(path, format);
+		let n = reader.header.n;
+		return (reader, n);
+	}
+	let mut reader = JobFileReader::open(path, JobFileFormat::Resume);
+	if !reader.header.frontier.is_empty() {
+		println!("error: resume file [{}] has a frontier of {} polycubes whose jobs haven't been found yet\n\
+			resume it until they have been, and halt it again, to write a resume file with all its jobs",
+			path.to_string_lossy(), reader.header.frontier.len());
+		exit(EXIT_ERROR);
+	}
+	let mut n = reader.header.spawn_n;
+	// (older resume files don't have spawn_n in the header)
+	if n == 0 {
+		if let Some(polycube_enc) = reader.peek() {
+			let mut polycube = Polycube::new(false);
+			polycube.rebuild_from_encoding(polycube_enc);
+			n = polycube.n;
+		}
+	}
+	(reader, n)
+}
 
-	// for each cube, for each direction, add a cube
-	// create a list to iterate over because the dict will change
-	//   during recursion within the loop
-	let original_positions: Vec<isize> = polycube.cube_info_by_pos.keys().cloned().collect();
-	// include all existing cubes' positions in the tried_pos set
-	tried_pos.extend(original_positions.iter());
-	for cube_pos in original_positions {
-		for direction_cost in DIRECTION_COSTS {
-			try_pos = cube_pos + direction_cost;
+// handle the "split" command
+// the jobs are dealt out to the parts in turn, so that neighboring
+//   jobs, which tend to take similar amounts of time, are spread out
+pub fn split_main(args: SplitArgs) {
+	let file_name = args.file.file_name().unwrap().to_string_lossy().to_string();
+	let stem = file_name.trim_end_matches(".gz").trim_end_matches(".txt");
+	let part_paths: Vec<String> = (1..args.parts+1)
+		.map(|i| args.file.with_file_name(format!("{}-part{}of{}.txt", stem, i, args.parts)).to_string_lossy().to_string())
+		.collect();
+	if let Some(existing) = part_paths.iter().find(|path| Path::new(path).exists()) {
+		println!("error: [{}] already exists", existing);
+		exit(EXIT_ERROR);
+	}
+	let (reader, n) = open_job_file(&args.file, if args.resume { JobFileFormat::Resume } else { JobFileFormat::Polycubes });
+	let header_counts = reader.header.n_counts.clone();
+	let header_n = reader.header.n;
+	let mut batches: Vec<Vec<u128>> = vec![Vec::new(); args.parts];
+	let mut part_job_counts: Vec<usize> = vec![0; args.parts];
+	for (i, polycube_enc) in reader.enumerate() {
+		let part = i % args.parts;
+		batches[part].push(polycube_enc);
+		part_job_counts[part] += 1;
+		if batches[part].len() >= POLYCUBES_FILE_BATCH {
+			write_polycubes_file(n, &part_paths[part], &batches[part]);
+			batches[part].clear();
+		}
+	}
+	for (part, batch) in batches.iter().enumerate() {
+		write_polycubes_file(n, &part_paths[part], batch);
+	}
+	println!();
+	for (path, job_count) in part_paths.iter().zip(part_job_counts.iter()) {
+		println!("wrote {} polycubes of size {} to [{}]", job_count, n, path);
+	}
+	if args.resume {
+		// the parts only have the jobs that were left, so the counts
+		//   the halted run already found are needed to finish the count
+		println!("count each part with: count -n {} --threads <threads> --begin-from-file <part>.gz, then add the counts for n={} from each part to the resume file's count of {}",
+			header_n, header_n, header_counts.get(&header_n).copied().unwrap_or(0));
+	}
+}
 
-			// skip if we've already tried this position
-			if !tried_pos.insert(try_pos) {
-				continue;
+// handle the "merge" command
+pub fn merge_main(args: MergeArgs) {
+	let output = args.output.to_string_lossy().to_string();
+	let mut merged_n: Option<u8> = None;
+	let mut written: usize = 0;
+	for file in args.files.iter() {
+		let (reader, n) = open_job_file(file, JobFileFormat::Polycubes);
+		if merged_n.is_some_and(|merged_n| merged_n != n) {
+			println!("error: [{}] has polycubes of size {}, but the files before it have polycubes of size {}",
+				file.to_string_lossy(), n, merged_n.unwrap());
+			exit(EXIT_ERROR);
+		}
+		merged_n = Some(n);
+		let mut batch: Vec<u128> = Vec::new();
+		for polycube_enc in reader {
+			batch.push(polycube_enc);
+			if batch.len() >= POLYCUBES_FILE_BATCH {
+				write_polycubes_file(n, &output, &batch);
+				written += batch.len();
+				batch.clear();
 			}
+		}
+		write_polycubes_file(n, &output, &batch);
+		written += batch.len();
+	}
+	println!("\nwrote {} polycubes of size {} from {} files to [{}]", written, merged_n.unwrap(), args.files.len(), output);
+}
 
-			// create P+A
-			polycube.add(try_pos);
-
-			// skip if we've already seen some P+A with the same canonical representation
-			//   (comparing the bitwise int only)
-			canonical_try = polycube.find_canonical_info_with_target(try_pos, canonical_orig_enc_shifted);
-			if !tried_canonicals.insert(canonical_try.enc) {
-				polycube.remove(try_pos);
-				continue;
+// handle the "convert" command
+pub fn convert_main(args: ConvertArgs) {
+	if args.output.exists() {
+		usage_error("convert", format!("<output> [{}] already exists", args.output.to_string_lossy()));
+	}
+	if args.to == ConvertFormat::Polycubes {
+		if let Err(err) = validate_polycubes_file_arg(&args.output.to_string_lossy(), "<output>") {
+			usage_error("convert", err);
+		}
+	}
+	let format = match (args.resume, args.legacy) {
+		(true, _) => JobFileFormat::Resume,
+		(false, true) => JobFileFormat::LegacyPolycubes,
+		(false, false) => JobFileFormat::Polycubes
+	};
+	let (reader, n) = open_job_file(&args.file, format);
+	let output = args.output.to_string_lossy().to_string();
+	let mut written: usize = 0;
+	if args.to == ConvertFormat::Polycubes {
+		let mut batch: Vec<u128> = Vec::new();
+		for polycube_enc in reader {
+			batch.push(polycube_enc);
+			if batch.len() >= POLYCUBES_FILE_BATCH {
+				write_polycubes_file(n, &output, &batch);
+				written += batch.len();
+				batch.clear();
 			}
-
-			least_significant_cube_pos = canonical_try.least_significant_cube_pos;
-
-			// if try_pos (cube A) is the least significant, then P+A-A==P and P+A is a new unique polycube
-			if least_significant_cube_pos == try_pos {
-				extend_single_thread(polycube,  limit_n, depth+1);
-			} else {
-				canonical_try_clone = canonical_try.clone();
-				// remove the last of the ordered cubes (cube B) in P+A
-				polycube.remove(least_significant_cube_pos);
-				// if P+A-B has the same canonical representation as P, count it as a new unique polycube
-				//   and continue recursion into that P+A
-				if polycube.find_canonical_enc_with_target(canonical_orig_enc) == canonical_orig_enc {
-					// replace the least significant cube we just removed
-					polycube.add(least_significant_cube_pos);
-					// replace the canonical info from before
-					polycube.canonical_info = Some(canonical_try_clone);
-					// continue recursion
-					extend_single_thread(polycube,  limit_n, depth+1);
-
-				// undo the temporary removal of the least significant cube,
-				//   but only if it's not the same as the cube we just tried
-				//   since we remove that one before going to the next iteration
-				//   of the loop
+		}
+		write_polycubes_file(n, &output, &batch);
+		written += batch.len();
+	} else {
+		write_file_atomically(&args.output, "converted file", |buf| {
+			for polycube_enc in reader {
+				let line = if args.to == ConvertFormat::Decimal {
+					polycube_enc.to_string()
 				} else {
-					polycube.add(least_significant_cube_pos);
-				}
+					let mut polycube = Polycube::new(false);
+					polycube.rebuild_from_encoding(polycube_enc);
+					normalized_coordinates(polycube.coordinates()).iter()
+						.map(|(x, y, z)| format!("{},{},{}", x, y, z))
+						.collect::<Vec<String>>().join(" ")
+				};
+				buf.write_all(format!("{}\n", line).as_bytes())?;
+				written += 1;
 			}
+			Ok(())
+		});
+	}
+	println!("\nwrote {} polycubes of size {} to [{}]", written, n, output);
+}
 
-			// revert creating P+A to try adding a cube at another position
-			polycube.remove(try_pos);
-		}
+// handle the "check" command, which exits with 0 only if
+//   the resume file passes every check
+pub fn check_main(args: CheckArgs) -> ! {
+	let problems = check_resume_file(&args.resume_file).problems;
+	if !problems.is_empty() {
+		println!("found {} problem(s)", problems.len());
+		exit(EXIT_CHECK_FAILED);
 	}
+	println!("ok");
+	exit(0);
 }
 
-pub fn write_resume_file(n: u8, spawn_n: u8, polycubes_to_write_to_disk: Vec<Polycube>, elapsed_sec: f64) {
-	let timestamp = Local::now().to_rfc3339().replace('-', "").replace(':', "");
-	let filename = format!("halt-n{}-{}.txt", n, &timestamp[0..15]);
-	let resume_file_path = create_executable_sibling_file(filename.as_str());
-	println!("writing {} polycubes to [{}]...", polycubes_to_write_to_disk.len(), resume_file_path.to_str().unwrap());
-	let file_buf = File::create(resume_file_path).unwrap();
-	// i am getting strange repeated/missing characters in the .gz file,
-	//   so i am trying a lower compression level
-	//let mut gz = GzEncoder::new(&mut file_buf, Compression::best());
-	// still getting errors at the default compression level
-	//let mut gz = GzEncoder::new(&mut file_buf, Compression::default());
-	// still getting errors at the fast compression level
-	//let mut gz = GzEncoder::new(&mut file_buf, Compression::fast());
-	// this works, but there's no point in creating a .gz file without compression
-	//let mut gz = GzEncoder::new(&mut file_buf, Compression::none());
-	let mut gz = BufWriter::new(file_buf);
-	match gz.write(format!("{}\n{}\n{}\n", n, spawn_n, elapsed_sec).as_bytes()) {
-		Ok(_) => {}
-		Err(err) => {
-			println!("error writing to resume file:\n{}", err);
-			// if we have an error writing resume file, there's no
-			//   point in continuing
-			exit(1);
-		}
+// handle the "check-canonicalizers" command
+pub fn check_canonicalizers_main(args: CheckCanonicalizersArgs) -> ! {
+	println!("checking canonicalizers [{}] against [{}] with {} random polycubes (lanes use {:?})...",
+		CANONICALIZERS.iter().skip(1).map(|c| c.name()).collect::<Vec<&str>>().join(", "),
+		CANONICALIZERS[0].name(),
+		args.count,
+		lane_backend());
+	let mut mismatches = check_canonicalizers(args.count);
+	if let Some(n) = args.counts {
+		println!("checking the counts up to n={} found with each canonicalizer...", n);
+		mismatches += check_canonicalizer_counts(n);
+		println!("checking the counts up to n={} found with and without reusing each polycube's best start cube...", n);
+		mismatches += check_reused_start_counts(&[Polycube::new(true).find_canonical_info(None).enc], n);
 	}
-	unsafe {
-		match gz.write(format!("{}\n", N_COUNTS.iter().enumerate().map(|(i,count)| format!("{}={}", i, count)).collect::<Vec<String>>().join(",")).as_bytes()) {
-			Ok(_) => {}
-			Err(err) => {
-				println!("error writing to resume file:\n{}", err);
-				// if we have an error writing resume file, there's no
-				//   point in continuing
-				exit(1);
-			}
-		}
+	if mismatches > 0 {
+		println!("found {} mismatches", mismatches);
+		exit(EXIT_CHECK_FAILED);
 	}
-	for polycube in polycubes_to_write_to_disk.iter() {
-		let cubes = polycube.cube_info_by_pos.keys().map(|pos| match pos.to_string().as_str() {
-			"--401" => {
-				panic!("the postion {} is generating a string of --401", pos);
-			}
-			_ => {
-				if pos.to_string().starts_with("--") {
-					panic!("the postion {} is generating a string of {}", pos, pos.to_string());
-				}
-				pos.to_string()
+	println!("all canonicalizers agree");
+	println!("timing each canonicalizer on {} other random polycubes...", args.count);
+	for (c, seconds) in CANONICALIZERS.iter().zip(time_canonicalizers(args.count)) {
+		println!("  {:<16} {:.3} seconds ({:.0} polycubes/sec)", c.name(), seconds, args.count as f64 / seconds);
+	}
+	exit(0);
+}
+
+// handle the "estimate" command
+pub fn estimate_main(args: EstimateArgs) {
+	args.canonicalizer.select();
+	let start_time = Instant::now();
+	let sums = knuth_estimate(args.n, args.samples, args.threads);
+	println!("estimated counts from {} random walks, in {:.3} seconds:", args.samples, start_time.elapsed().as_secs_f64());
+	for i in 1..args.n as usize + 1 {
+		let estimate = sums[i].mean();
+		let std_err = (sums[i].variance() / sums[i].samples).sqrt();
+		let well_known = WELL_KNOWN_N_COUNTS[i] as f64;
+		// how far off the well-known count is, in standard errors
+		let deviation = if std_err > 0.0 {
+			format!("{:+.2} std errs", (estimate - well_known) / std_err)
+		} else {
+			format!("{:+.0}", estimate - well_known)
+		};
+		println!("n = {: >2}: {:.0} (std err {:.1}, {:.2}%), well-known{}: {} ({})",
+			i, estimate, std_err, 100.0 * std_err / estimate,
+			if i > 20 { " estimate" } else { "" },
+			WELL_KNOWN_N_COUNTS[i], deviation);
+	}
+}
+
+// handle the "sample" command
+pub fn sample_main(args: SampleArgs) {
+	let seed = args.seed.unwrap_or_else(|| thread_rng().gen());
+	// (the seed goes to stderr so that stdout is only the samples)
+	eprintln!("seed: {}", seed);
+
+	if args.check_uniformity {
+		if args.n > 8 {
+			usage_error("sample", String::from("<n> must be no more than 8 to check uniformity"));
+		}
+		match check_sampler_uniformity(args.n, args.count, args.walks, seed, args.threads) {
+			Some(p_value) if p_value >= 0.001 => {
+				println!("ok: p-value={:.4}", p_value);
+				exit(0);
 			}
-		}).collect::<Vec<String>>().join(",");
-		match gz.write(format!("{}\n", cubes).as_bytes()) {
-			Ok(_) => {}
-			Err(err) => {
-				println!("error writing to resume file:\n{}", err);
-				// if we have an error writing resume file, there's no
-				//   point in continuing
-				exit(1);
+			Some(p_value) => {
+				println!("error: the samples are not uniform: p-value={:.6}", p_value);
+				exit(EXIT_CHECK_FAILED);
 			}
+			None => exit(EXIT_CHECK_FAILED)
 		}
 	}
-	match gz.write("--end--".as_bytes()) {
-		Ok(_) => {}
-		Err(err) => {
-			println!("error writing to resume file:\n{}", err);
-			// if we have an error writing resume file, there's no
-			//   point in continuing
-			exit(1);
-		}
+	let start_time = Instant::now();
+	let samples = sample_polycubes(args.n, args.count, args.walks, seed, args.threads);
+	let stdout = std::io::stdout();
+	let mut out = BufWriter::new(stdout.lock());
+	for (polycube_enc, _effective_walks) in samples.iter() {
+		writeln!(out, "{}", encoding_to_str(*polycube_enc)).unwrap();
 	}
-	gz.flush().unwrap();
+	out.flush().unwrap();
+	// (how close to uniform the samples are, see sample_polycube)
+	let fewest_effective_walks = samples.iter().map(|(_polycube_enc, effective_walks)| *effective_walks).fold(f64::INFINITY, f64::min);
+	eprintln!("sampled {} polycubes in {:.3} seconds, each from {} walks worth at least {:.1} uniform ones",
+		samples.len(), start_time.elapsed().as_secs_f64(), args.walks, fewest_effective_walks);
 }
 
-// a "resume file" contains partial results from a halted run
-pub fn read_resume_file(resume_file_path: &PathBuf)
-		-> (u8, u8, BTreeMap<u8, usize>, f64, Vec<u128>) {
-	let file_err_msg: String = format!("error reading resume file [{}]", resume_file_path.to_string_lossy());
-	let f = File::open(resume_file_path).expect(file_err_msg.as_str());
-	let file_extension = resume_file_path.extension().unwrap();
-	if file_extension != "gz" /*&& file_extension != "txt" */ {
-		println!("unsupported resume file extension of [{:?}]", resume_file_path);
-		exit(1);
+// handle the "subtree" command
+pub fn subtree_main(args: SubtreeArgs) {
+	let mut seed = polycube_from_encoding_str(&args.seed).unwrap();
+	if args.n <= seed.n {
+		usage_error("subtree", format!("<n> must be greater than the seed's size ({})", seed.n));
 	}
-	let mut buf = BufReader::new(GzDecoder::new(f));
-	//if file_extension == "gz" {
-	//	BufReader::new(GzDecoder::new(f))
-	//} else  {
-	//	BufReader::new(f)
-	//};
-	let mut n: u8 = 0;
-	let mut spawn_n: u8 = 0;
-	let mut previous_total_elapsed_sec: f64 = 0.0;
-	let mut n_counts: BTreeMap<u8, usize> = BTreeMap::new();
-	let mut polycubes_read: Vec<u128> = Vec::new();
-	let mut line: String = String::new();
-	let mut line_num: usize = 0;
-	let mut len: usize = 1;
-	while len > 0 {
-		line.clear();
-		len = buf.read_line(&mut line).expect(file_err_msg.as_str());
-		line = line.trim().to_string();
-		if line == "--end--" {
-			break;
-		}
-		if line_num == 0 {
-			//println!("using line [{}] as n", line);
-			// first line is the arg_n
-			n = line.parse().unwrap();
-		} else if line_num == 1 {
-			//println!("using line [{}] as spawn_n", line);
-			// second line is the arg_spawn_n
-			spawn_n  = line.parse().unwrap();
-		} else if line_num == 2 {
-			//println!("using line [{}] as previous_total_elapsed_sec", line);
-			// third line is the previous_total_elapsed_sec
-			previous_total_elapsed_sec = line.parse().unwrap();
-		} else if line_num == 3 {
-			//println!("using line [{}] as n_counts", line);
-			// fourth line is the n_counts: "1=1,2=1,3=2,..."
-			for item in line.split(',')
-					.map(|item| item.split_once('=')) {
-				let (n, count) = item.unwrap();
-				if count != "0" {
-					println!("    n = {: >2}: {}", n, count);
-				}
-				n_counts.insert(n.parse().unwrap(), count.parse().unwrap());
-			}
-		} else if line_num > 3 && line.len() > 0 {
-			// lines 5 and beyond are polycube base94 encodings, one polycube per line
-			polycubes_read.push(line.parse().unwrap());
-		}
-		line_num += 1;
-	}
-	println!("read {} polycubes from resume file", polycubes_read.len());
-	return (n, spawn_n, n_counts, previous_total_elapsed_sec, polycubes_read);
+	let spawn_n = args.spawn_n.unwrap_or(seed.n + 2);
+	if args.threads > 0 && (spawn_n <= seed.n || spawn_n >= args.n) {
+		usage_error("subtree", format!("<spawn-n> must be greater than the seed's size ({}) and less than <n>", seed.n));
+	}
+
+	let start_time = Instant::now();
+	let seed_enc = seed.find_canonical_info(None).enc;
+	let counts = subtree_counts(seed_enc, args.n, args.threads, spawn_n);
+	println!("\nsubtree of [{}] (n={}):", encoding_to_str(seed_enc), seed.n);
+	for i in seed.n..args.n+1 {
+		println!("n = {: >2}: {}", i, counts[i as usize]);
+	}
+	println!("elapsed seconds: {:.6}", start_time.elapsed().as_secs_f64());
 }
 
-// a polycubes file contains a list of polycube encodings
-//   where each line is a polycube encoding, except for
-//   the first line, which is n
-pub fn write_polycubes_file(
-		n: u8,
-		polycubes_file_path: &str,
-		polycubes_to_write_to_disk: &Vec<u128>) {
-	let polycubes_file_path: PathBuf = PathBuf::from(polycubes_file_path);
-	println!("\nwriting {} polycubes to [{}]...", polycubes_to_write_to_disk.len(), polycubes_file_path.to_str().unwrap());
-	let file_already_exists = polycubes_file_path.exists();
-	// append to the file if it already exists
-	let file_buf = if file_already_exists {
-		File::options().append(true).open(polycubes_file_path).unwrap()
-	// if we're creating a new file, write n as the first line
-	} else {
-		File::create(polycubes_file_path).unwrap()
+// handle the "inspect" command, which describes a single polycube
+pub fn inspect_main(args: InspectArgs) {
+	let parsed = match (args.polycube, args.coordinates) {
+		(Some(polycube), _) => polycube_from_encoding_str(&polycube),
+		(None, coordinates) => parse_coordinates(&coordinates.unwrap_or_default())
+			.and_then(|coordinates| Polycube::from_coordinates(&normalized_coordinates(coordinates)))
 	};
-	let mut buf = BufWriter::new(file_buf);
-	// if creating a new file, write n as the first line
-	if !file_already_exists {
-		match buf.write(format!("{}\n", n).as_bytes()) {
-			Ok(_) => {}
-			Err(err) => {
-				println!("error writing to polycubes file:\n{}", err);
-				// if we have an error writing polycubes file, there's no
-				//   point in continuing
-				exit(1);
-			}
-		}
-	}
-	for polycube_enc in polycubes_to_write_to_disk {
-		let base94 = encoding_to_str(*polycube_enc);
-		//let back_to_int = str_to_encoding(&base94);
-		//if back_to_int != *polycube_enc {
-		//	println!("ERROR! for pcube encoding [{}], we have base94 [{}] which converts back to [{}]", polycube_enc, base94, back_to_int);
-		//	exit(1);
-		//}
-		//let mut p = Polycube::new(false);
-		//p.rebuild_from_encoding(*polycube_enc);
-		//let recreated_enc = p.find_canonical_info(IMPOSSIBLE_POS).enc;
-		//if  recreated_enc != *polycube_enc {
-		//	println!("ERROR! for pcube encoding [{}], we have recreated a polycube of size [{}] with encoding [{}]", polycube_enc, p.n, recreated_enc);
-		//} else {
-		//	println!("for pcube encoding [{}], we have recreated a polycube of size [{}] with encoding [{}]", polycube_enc, p.n, recreated_enc);
-		//}
-		match buf.write(format!("{}\n", base94).as_bytes()) {
-			Ok(_) => {}
-			Err(err) => {
-				println!("error writing to polycubes file:\n{}", err);
-				// if we have an error writing polycubes file, there's no
-				//   point in continuing
-				exit(1);
-			}
+	let mut polycube = match parsed {
+		Ok(polycube) if (polycube.n as usize) < MAX_N - 1 => polycube,
+		Ok(polycube) => {
+			// (the encoding only has room for 21 cubes)
+			usage_error("inspect", format!("{} cubes is more than the limit of {}", polycube.n, MAX_N - 2));
+		}
+		Err(err) => usage_error("inspect", err)
+	};
+	let enc = polycube.find_canonical_info(None).enc;
+	println!("n: {}", polycube.n);
+	println!("canonical encoding: {} (hex: {:#x}, base94: {})", enc, enc, encoding_to_str(enc));
+	let coordinates = normalized_coordinates(polycube.coordinates());
+	println!("coordinates: {}", coordinates.iter().map(|(x, y, z)| format!("{},{},{}", x, y, z)).collect::<Vec<String>>().join(" "));
+	let extent = |axis: fn(&(isize, isize, isize)) -> isize| coordinates.iter().map(axis).max().unwrap() + 1;
+	let (size_x, size_y, size_z) = (extent(|c| c.0), extent(|c| c.1), extent(|c| c.2));
+	println!("bounding box: {}x{}x{}", size_x, size_y, size_z);
+	// each layer is drawn with y increasing upward
+	for z in 0..size_z {
+		println!("z = {}:", z);
+		for y in (0..size_y).rev() {
+			let row: String = (0..size_x).map(|x| if coordinates.contains(&(x, y, z)) { '#' } else { '.' }).collect();
+			println!("  {}", row);
 		}
 	}
-	buf.flush().unwrap();
+	let symmetries = polycube.symmetries();
+	println!("rotation group: {} (order {})", rotation_group_name(&symmetries), symmetries.len());
+	let mirror_enc = polycube.mirrored().find_canonical_info(None).enc;
+	if mirror_enc == enc {
+		println!("chiral: no");
+	} else {
+		println!("chiral: yes, the mirror image's encoding is {}", encoding_to_str(mirror_enc));
+	}
+	println!("faces: {}", polycube.face_count());
+	match polycube.parent() {
+		Some(mut parent) => println!("parent: {}", encoding_to_str(parent.find_canonical_info(None).enc)),
+		None => println!("parent: none")
+	}
+	println!("children: {}", polycube.children().len());
 }
 
-// a polycubes file contains a list of polycube encodings
-//   where each line is a polycube encoding, except for
-//   the first line, which is n
-pub fn read_polycubes_file(resume_file_path: &PathBuf)
-		-> (u8, Vec<u128>) {
-	let file_err_msg: String = format!("error reading resume file [{}]", resume_file_path.to_string_lossy());
-	let f = File::open(resume_file_path).expect(file_err_msg.as_str());
-	let file_extension = resume_file_path.extension().unwrap();
-	if file_extension != "gz" {
-		println!("unsupported resume file extension of [{:?}]", resume_file_path);
-		exit(1);
-	}
-	let mut buf = BufReader::new(GzDecoder::new(f));
-	let mut n: u8 = 0;
-	let mut polycubes_read: Vec<u128> = Vec::new();
-	let mut line: String = String::new();
-	let mut first_line = true;
-	let mut len: usize = 1;
-	while len > 0 {
-		line.clear();
-		len = buf.read_line(&mut line).expect(file_err_msg.as_str());
-		line = line.trim().to_string();
-		if line == "--end--" {
-			break;
+// parse coordinates given as x,y,z separated by spaces or semicolons
+pub fn parse_coordinates(s: &str) -> Result<Vec<(isize, isize, isize)>, String> {
+	s.split(|c: char| c.is_whitespace() || c == ';').filter(|item| !item.is_empty()).map(|item| {
+		let values: Vec<Result<isize, _>> = item.split(',').map(|value| value.trim().parse::<isize>()).collect();
+		match values.as_slice() {
+			[Ok(x), Ok(y), Ok(z)] => Ok((*x, *y, *z)),
+			_ => Err(format!("[{}] is not a coordinate (expected x,y,z)", item))
 		}
-		if first_line {
-			n = line.parse().unwrap();
-			first_line = false;
-		} else if line.len() > 0 {
-			polycubes_read.push(str_to_encoding(line.as_str()));
-		}
-	}
-	println!("read {} polycubes from polycubes file", polycubes_read.len());
-	return (n, polycubes_read);
+	}).collect()
 }
 
-pub fn create_executable_sibling_file(filename: &str) -> PathBuf {
-	return match env::current_exe() {
-		Ok(executable_path) => {
-			executable_path.parent().unwrap().join(filename)
+// handle the "parent" command, which prints a polycube's parent (or
+//   all of its ancestors) in the canonical extension tree
+pub fn parent_main(args: ParentArgs) {
+	let mut polycube = polycube_from_encoding_str(&args.polycube.polycube).unwrap();
+	if args.ancestry {
+		for ancestor in polycube.ancestry().iter_mut() {
+			let ancestor_enc = ancestor.find_canonical_info(None).enc;
+			println!("n = {: >2}: {}", ancestor.n, encoding_to_str(ancestor_enc));
 		}
-		Err(_) => {
-			println!("error: could not determine current executable path");
-			exit(1);
+		return;
+	}
+	match polycube.parent() {
+		Some(mut parent) => println!("{}", encoding_to_str(parent.find_canonical_info(None).enc)),
+		None => {
+			println!("error: the monocube has no parent");
+			exit(EXIT_ERROR);
 		}
-	};
+	}
 }
 
-pub fn seconds_to_dur(s: f64) -> String {
-	let days = (s / 86400.0).floor();
-	let hours = ((s - (days * 86400.0)) / 3600.0).floor();
-	let minutes = ((s - (days * 86400.0) - (hours * 3600.0)) / 60.0).floor();
-	let seconds = s - (days * 86400.0) - (hours * 3600.0) - (minutes * 60.0);
-	let fsec = format!("{}{:.3}", if seconds < 10.0 { "0" } else { "" }, seconds);
-	if days > 0.0 {
-		return format!("{} days {:0>2}h:{:0>2}m:{}s", days, hours, minutes, fsec);
+// handle the "children" command, which prints a polycube's children
+//   in the canonical extension tree, in increasing order of their
+//   canonical encodings (which is their order in the enumeration)
+pub fn children_main(args: PolycubeArg) {
+	let mut polycube = polycube_from_encoding_str(&args.polycube).unwrap();
+	if polycube.n as usize >= MAX_N - 1 {
+		usage_error("children", format!("the children of a polycube of {} cubes are larger than the limit of {}", polycube.n, MAX_N - 1));
+	}
+	for child in polycube.children().iter_mut() {
+		println!("{}", encoding_to_str(child.find_canonical_info(None).enc));
 	}
-	return format!("{:0>2}h:{:0>2}m:{}s", hours, minutes, fsec);
 }
 
-pub fn validate_polycubes_file_arg(file_arg: &str, arg_name: &str) -> Result<PathBuf, String> {
-	let file_path = PathBuf::from(file_arg);
-	match file_path.try_exists() {
-		Ok(_) => {}
-		Err(_) => {
-			return Err(format!("{} [{}] does not have permissions necessary for access", arg_name, file_arg));
+// handle the "rank" command, which prints polycubes' sizes and ranks
+// the rank is the polycube's index among all the polycubes of its
+//   size, in the order of a depth-first walk of the canonical
+//   extension tree
+pub fn rank_main(args: RankArgs) {
+	for polycube in args.polycubes.iter() {
+		let polycube = polycube_from_encoding_str(polycube).unwrap();
+		println!("n={} rank={}", polycube.n, rank(&polycube));
+	}
+}
+
+// handle the "unrank" command, which prints the polycubes with the given ranks
+pub fn unrank_main(args: UnrankArgs) {
+	for r in args.rank {
+		match unrank(args.n, r) {
+			Some(mut polycube) => println!("{}", encoding_to_str(polycube.find_canonical_info(None).enc)),
+			None => {
+				println!("error: there are no more than {} polycubes of size {}", r, args.n);
+				exit(EXIT_ERROR);
+			}
 		}
 	}
-	if file_path.is_dir() {
-		return Err(format!("{} [{}] is not a regular file or does not have permissions \
-			necessary for access", arg_name, file_arg));
+}
+
+// handle the "bench" command
+pub fn bench_main(args: BenchArgs) {
+	args.canonicalizer.select();
+	let bench_results = run_bench(&bench_matrix(args.quick), args.warmup, args.repetitions);
+	let output_path = args.output.unwrap_or_else(|| create_executable_sibling_file(
+		format!("bench-{}.json", Utc::now().format("%Y%m%d-%H%M%S")).as_str()));
+	let json = serde_json::to_string_pretty(&bench_results).unwrap();
+	match std::fs::write(&output_path, json + "\n") {
+		Ok(_) => println!("\nwrote benchmark results to [{}]", output_path.to_string_lossy()),
+		Err(err) => {
+			println!("error: failed to write benchmark results to [{}]: {}", output_path.to_string_lossy(), err);
+			exit(EXIT_ERROR);
+		}
 	}
-	if !file_path.extension().unwrap().eq("txt") {
-		return Err(format!("{} [{}] must be a .txt file", arg_name, file_arg));
+}
+
+fn main() {
+	// (the matches say which settings came from environment variables)
+	let matches = Cli::command().get_matches_from(with_legacy_subcommand(env::args().collect()));
+	let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+	let subcommand_matches = matches.subcommand().map(|(_name, subcommand_matches)| subcommand_matches).unwrap();
+	match cli.command {
+		CliCommand::Count(args) => count_main(args, subcommand_matches),
+		CliCommand::Resume(args) => resume_main(args, subcommand_matches),
+		CliCommand::GenerateJobs(args) => generate_jobs_main(args),
+		CliCommand::Split(args) => split_main(args),
+		CliCommand::Merge(args) => merge_main(args),
+		CliCommand::Convert(args) => convert_main(args),
+		CliCommand::Inspect(args) => inspect_main(args),
+		CliCommand::Parent(args) => parent_main(args),
+		CliCommand::Children(args) => children_main(args),
+		CliCommand::Rank(args) => rank_main(args),
+		CliCommand::Unrank(args) => unrank_main(args),
+		CliCommand::Subtree(args) => subtree_main(args),
+		CliCommand::Estimate(args) => estimate_main(args),
+		CliCommand::Sample(args) => sample_main(args),
+		CliCommand::Check(args) => check_main(args),
+		CliCommand::CheckCanonicalizers(args) => check_canonicalizers_main(args),
+		CliCommand::Bench(args) => bench_main(args)
 	}
-	Ok(file_path)
 }
 
-pub fn validate_resume_file_arg(file_arg: &str, arg_name: &str) -> Result<PathBuf, String> {
-	let file_path = PathBuf::from(file_arg);
-	if !file_path.is_file() {
-		return Err(format!("{} [{}] is not a regular file, does not exist, or does not have permissions \
-			necessary for access", arg_name, file_arg));
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn base94_round_trip() {
+		let mut rng = StdRng::seed_from_u64(1);
+		for enc in [0, 1, 93, 94, 95, 94 * 94, u128::MAX].into_iter().chain((0..1000).map(|_| rng.gen::<u128>())) {
+			assert_eq!(str_to_encoding(&encoding_to_str(enc)), enc, "[{}]", encoding_to_str(enc));
+		}
+		assert_eq!(encoding_to_str(0), POLYCUBE_ENCODING_ALPHABET[0].to_string());
+	}
+
+	#[test]
+	fn canonicalizers_agree() {
+		assert_eq!(check_canonicalizers(200), 0);
+	}
+
+	#[test]
+	fn panicking_jobs_are_retried_then_quarantined() {
+		// three jobs of size 6 counted up to 8: the first panics once
+		//   and is counted on its retry, the second panics on every
+		//   attempt, and the third doesn't panic
+		let job_encs: Vec<u128> = (0..3).map(|i| canonical_enc(6, i * 50 + 3)).collect();
+		INJECTED_JOB_PANICS.with(|panics| {
+			panics.borrow_mut().insert(job_encs[0], 1);
+			panics.borrow_mut().insert(job_encs[1], JOB_ATTEMPTS);
+		});
+		let submit_queue = Arc::new(ArrayQueue::new(JOB_QUEUE_CAPACITY));
+		for enc in job_encs.iter() {
+			submit_queue.push(*enc).unwrap();
+		}
+		let response_queue = Arc::new(ArrayQueue::new(RESPONSE_QUEUE_CAPACITY));
+		// with nothing more to come, the worker stops once the jobs are done
+		extend_as_worker_outer(8, Arc::new(AtomicBool::new(false)), Arc::new(AtomicBool::new(true)),
+			Arc::new(AtomicBool::new(false)), submit_queue, response_queue.clone(), false);
+		INJECTED_JOB_PANICS.with(|panics| panics.borrow_mut().clear());
+
+		let responses: Vec<ThreadResponse> = std::iter::from_fn(|| response_queue.pop()).collect();
+		assert_eq!(responses.len(), 3);
+		for (enc, response) in job_encs.iter().zip(responses.iter()) {
+			if *enc == job_encs[1] {
+				assert!(!response.job_complete);
+				assert_eq!(response.orig_polycube_enc, Some(*enc));
+				assert!(response.failure.as_deref().unwrap().contains("injected panic"));
+			} else {
+				// a retried job's counts are only those of its successful attempt
+				let expected = subtree_counts(*enc, 8, 0, 0);
+				assert!(response.job_complete && response.failure.is_none());
+				assert_eq!(response.results.unwrap()[7..=8], expected[7..=8], "job [{}]", encoding_to_str(*enc));
+			}
+		}
 	}
-	if !file_path.extension().unwrap().eq("gz") {
-		return Err(format!("{} [{}] must be a .txt.gz file", arg_name, file_arg));
+
+	#[test]
+	fn full_queue_push_waits_for_room_or_halt() {
+		let queue: Arc<ArrayQueue<u128>> = Arc::new(ArrayQueue::new(2));
+		queue.push(1).unwrap();
+		queue.push(2).unwrap();
+		// a halt while the queue is full hands the item back
+		let atomic_halt = AtomicBool::new(true);
+		assert_eq!(push_with_backpressure(&queue, 3, Some(&atomic_halt)), Err(3));
+		assert_eq!(queue.len(), 2);
+		// otherwise the push waits until another thread makes room
+		atomic_halt.store(false, Ordering::Relaxed);
+		let push_start = Instant::now();
+		let consumer_queue = queue.clone();
+		let consumer = thread::spawn(move || {
+			thread::sleep(Duration::from_millis(50));
+			consumer_queue.pop()
+		});
+		assert_eq!(push_with_backpressure(&queue, 3, Some(&atomic_halt)), Ok(()));
+		assert!(push_start.elapsed() >= Duration::from_millis(50));
+		assert_eq!(consumer.join().unwrap(), Some(1));
+		assert_eq!(std::iter::from_fn(|| queue.pop()).collect::<Vec<u128>>(), vec![2, 3]);
 	}
-	Ok(file_path)
-}
 
-pub fn print_results(complete: bool, n: u8) {
-	unsafe {
-		println!("\n\n{}results:", if complete { "" } else { "partial " });
-		for i in 1..n+1 {
-			println!("n = {: >2}: {}", i, N_COUNTS[i as usize]);
-		}
+	#[test]
+	fn run_lock_is_exclusive_until_dropped() {
+		let path = std::env::temp_dir().join(format!("cubes-rust-test-run-{}.lock", std::process::id()));
+		let lock = RunLock::acquire(path.clone(), "the work directory").unwrap();
+		// a second run is turned away, and told which run holds the lock
+		let reason = RunLock::acquire(path.clone(), "the work directory").err().unwrap();
+		assert!(reason.starts_with("the work directory is in use by another run"), "{}", reason);
+		assert!(reason.contains(&format!("pid {}", std::process::id())), "{}", reason);
+		drop(lock);
+		// the file is left in place, and can be locked again once released
+		assert!(path.exists());
+		let lock = RunLock::acquire(path.clone(), "the work directory").unwrap();
+		drop(lock);
+		std::fs::remove_file(&path).unwrap();
 	}
-}
 
-fn main() {
-	let args: Vec<String> = env::args().collect();
-	let usage = format!("usage: {} \
-	[--n <n>] \
-	[--threads <threads>] \
-	[--spawn-n <spawn-n>] \
-	[--resume-from-file <resume-file>] \
-	[--begin-from-file <begin-file>] \
-	[--write-found-polycubes-file <polycubes-file>]\n\
-	where:\n\
-	-  <n>.............: the number of cubes the largest counted polycube should contain (>1)\n\
-	-  <threads>.......: 0 for single-threaded, or >1 for the maximum number of threads to spawn simultaneously (default=0)\n\
-	-  <spawn-n>.......: the polycube size to give to worker threads, where higher->more shorter-lived threads (8<=recommended<=12)\n\
-	-  <resume-file>...: a .txt.gz file previously created by this program\n\
-	-  <polycubes-file>: a .txt.gz file previously created by this program\n",
-	args[0]);
-	if args.len() < 3 {
-		println!("{}", usage);
-		exit(1);
-	}
-
-	let mut cursor: usize = 1;
-	let mut arg_n: u8 = 0;
-	let mut arg_threads: u8 = 0;
-	let mut arg_spawn_n: u8 = 0;
-	let mut arg_resume_file: Option<PathBuf> = None;
-	let mut arg_begin_file: Option<PathBuf> = None;
-	let mut arg_polycubes_file: Option<String> = None;
-	let mut arg_polycubes_file_path: &str = "none";
-	// we want to start at the 1th index, and advance by 2
-	while cursor < args.len() - 1 {
-		if args[cursor] == "--help" || args[cursor] == "-h" {
-			println!("{}", usage);
-			exit(1);
-		} else if args[cursor] == "--n" || args[cursor] == "-n" {
-			arg_n = match args[cursor + 1].parse() {
-				Ok(n) => {
-					if n < 2 {
-						println!("error: <n> must be greater than 1");
-						println!("{}", usage);
-						exit(1);
-					} else if n > 21 {
-						println!("error: <n> greater than 21 not yet ;) supported");
-						println!("{}", usage);
-						exit(1);
-					}
-					n
-				}
-				Err(_) => {
-					println!("error: invalid value for <n>");
-					println!("{}", usage);
-					exit(1);
-				}
-			};
-		} else if args[cursor] == "--threads" || args[cursor] == "-t" {
-			arg_threads = match args[cursor + 1].parse() {
-				Ok(threads) => {
-					if threads == 1 {
-						println!("error: <threads> must be 0 or greater than 1");
-						println!("{}", usage);
-						exit(1);
-					}
-					threads
-				}
-				Err(_) => {
-					println!("error: invalid value for <threads>");
-					println!("{}", usage);
-					exit(1);
-				}
-			};
-		} else if args[cursor] == "--spawn-n" || args[cursor] == "-s" {
-			arg_spawn_n = match args[cursor + 1].parse() {
-				Ok(spawn_n) => {
-					if spawn_n < 4 {
-						println!("error: <spawn-n> must be greater than 3");
-						println!("{}", usage);
-						exit(1);
-					}
-					spawn_n
-				}
-				Err(_) => {
-					println!("error: invalid value for <spawn-n>");
-					println!("{}", usage);
-					exit(1);
-				}
-			};
-		} else if args[cursor] == "--resume-from-file" || args[cursor] == "-r" {
-			arg_resume_file = match validate_resume_file_arg(&args[cursor + 1], "<resume-file>") {
-				Ok(path) => Some(path),
-				Err(err) => {
-					println!("error: {}", err);
-					println!("{}", usage);
-					exit(1);
-				}
-			};
-		} else if args[cursor] == "--begin-from-file" || args[cursor] == "-b" {
-			arg_begin_file = match validate_resume_file_arg(&args[cursor + 1], "<begin-file>") {
-				Ok(path) => Some(path),
-				Err(err) => {
-					println!("error: {}", err);
-					println!("{}", usage);
-					exit(1);
-				}
-			};
-		} else if args[cursor] == "--write-found-polycubes-file" || args[cursor] == "-w" {
-			arg_polycubes_file = match validate_polycubes_file_arg(&args[cursor + 1], "<polycubes-file>") {
-				Ok(_) => {
-					arg_polycubes_file_path = &args[cursor + 1];
-					Some(String::from(arg_polycubes_file_path))
-				}
-				Err(err) => {
-					println!("error: {}", err);
-					println!("{}", usage);
-					exit(1);
-				}
-			};
-		} else {
-			println!("error: unknown argument [{}]", args[cursor]);
-			println!("{}", usage);
-			exit(1);
-		}
-		cursor += 2;
+	#[test]
+	fn reused_start_counts_agree() {
+		// the subtrees of a spread of polycubes with 9 cubes, up to 13
+		let count = WELL_KNOWN_N_COUNTS[9] as u128;
+		let polycube_encs: Vec<u128> = (0..4).map(|i| canonical_enc(9, i * count / 4 + 7)).collect();
+		assert_eq!(check_reused_start_counts(&polycube_encs, 13), 0);
 	}
-	if arg_spawn_n > 0 {
-		// cannot specify both a <spawn-n> and a <resume-file> or <begin-file>
-		if arg_resume_file.is_some() || arg_begin_file.is_some() {
-			println!("error: cannot specify either a <resume-file> or <begin-file> when a <spawn-n> is specified");
-			println!("{}", usage);
-			exit(1);
+
+	#[test]
+	fn rank_unrank_round_trip() {
+		for n in 1..8u8 {
+			let count = WELL_KNOWN_N_COUNTS[n as usize] as u128;
+			// every rank up to n=4, and a spread of them above that
+			let step = if n <= 4 { 1 } else { count as usize / 8 };
+			for r in (0..count).step_by(step).chain([count - 1]) {
+				let polycube = unrank(n, r).unwrap();
+				assert_eq!(polycube.n, n);
+				assert_eq!(rank(&polycube), r, "n={} rank={}", n, r);
+			}
+			assert!(unrank(n, count).is_none(), "n={}", n);
 		}
-		if arg_spawn_n >= arg_n {
-			println!("error: <spawn-n> must be less than <n>");
-			println!("{}", usage);
-			exit(1);
+	}
+
+	#[test]
+	fn cached_subtree_counts() {
+		let monocube_enc = Polycube::new(true).find_canonical_info(None).enc;
+		for n in [3, 8, 9] {
+			assert_eq!(cached_subtree_count(monocube_enc, n), WELL_KNOWN_N_COUNTS[n as usize] as u128, "n={}", n);
 		}
+		// every child's count is kept, and adds up to its parent's
+		let child_encs = sorted_canonical_children(&mut Polycube::new(true));
+		let cache = SUBTREE_COUNT_CACHE.lock().unwrap();
+		assert_eq!(child_encs.iter().map(|child_enc| cache[&(*child_enc, 9)]).sum::<u128>(), cache[&(monocube_enc, 9)]);
 	}
-	if arg_n > 0 {
-		if arg_threads > 0 && arg_spawn_n < 1 && arg_resume_file.is_none() && arg_begin_file.is_none() {
-			println!("error: <spawn-n> must be greater than 0 when <threads> is greater than 0");
-			println!("{}", usage);
-			exit(1);
+
+	#[test]
+	fn parse_coordinates_separators() {
+		let expected = vec![(0, 0, 0), (1, 0, 0), (1, -2, 30)];
+		for s in ["0,0,0 1,0,0 1,-2,30", "0,0,0;1,0,0;1,-2,30", " 0,0,0 ;\n1,0,0  1,-2,30; "] {
+			assert_eq!(parse_coordinates(s), Ok(expected.clone()), "[{}]", s);
 		}
+		assert_eq!(parse_coordinates(""), Ok(Vec::new()));
 	}
-	if arg_resume_file.is_some() && arg_begin_file.is_some() {
-		println!("error: cannot specify both <resume-file> and <begin-file>");
-		println!("{}", usage);
-		exit(1);
+
+	#[test]
+	fn parse_coordinates_errors() {
+		// (spaces separate coordinates, so they can't be inside one)
+		for s in ["0,0", "0,0,0,0", "0,0,x", "0,0,0 1,0", "0;0;0", "0, 0, 0"] {
+			assert!(parse_coordinates(s).is_err(), "[{}]", s);
+		}
 	}
-	// we either need a <resume-file> or a value for <n>
-	match arg_resume_file.as_ref() {
-		Some(resume_file_path) => {
-			println!("resuming from file: {}", resume_file_path.to_string_lossy());
+
+	#[test]
+	fn coordinates_round_trip() {
+		let mut rng = StdRng::seed_from_u64(3);
+		for _ in 0..50 {
+			let mut polycube = random_polycube(rng.gen_range(1..12), &mut rng);
+			let s = polycube.coordinates().iter().map(|(x, y, z)| format!("{},{},{}", x, y, z)).collect::<Vec<String>>().join(" ");
+			let mut parsed = Polycube::from_coordinates(&parse_coordinates(&s).unwrap()).unwrap();
+			assert_eq!(parsed.find_canonical_info(None).enc, polycube.find_canonical_info(None).enc, "[{}]", s);
 		}
-		None => {
-			if arg_n == 0 {
-				println!("error: n must be specified");
-				println!("{}", usage);
-				exit(1);
+	}
+
+	#[test]
+	fn symmetry_moves_match_encoding() {
+		for r in 0..WELL_KNOWN_N_COUNTS[6] as u128 {
+			let mut polycube = unrank(6, r).unwrap();
+			let symmetry_moves = polycube.find_symmetry_moves();
+			// the start cube and rotation pairs that give the encoding
+			//   are the polycube's symmetries
+			assert_eq!(symmetry_moves.len() + 1, polycube.symmetry_order() as usize, "rank {}", r);
+			let positions: BTreeSet<isize> = polycube.cube_info_by_pos.keys().cloned().collect();
+			for symmetry_move in symmetry_moves.iter() {
+				let moved: BTreeSet<isize> = positions.iter().map(|pos| symmetry_move.moved(*pos)).collect();
+				assert_eq!(moved, positions, "rank {}", r);
 			}
 		}
 	}
 
-	let halt_file_path = create_executable_sibling_file("halt-signal.txt");
-	if halt_file_path.exists() {
-		println!("found halt file [{}] already exists, stopping...", halt_file_path.to_str().unwrap());
-		exit(0);
+	fn canonical_enc(n: u8, r: u128) -> u128 {
+		unrank(n, r).unwrap().find_canonical_info(None).enc
 	}
 
-	let mut previous_total_elapsed_sec: f64 = 0.0;
-	let mut complete = false;
-	let start_time = Instant::now();
-	let mut last_count_increment_time: Option<Instant> = None;
-	if arg_threads == 0 {
-		extend_single_thread(&mut Polycube::new(true), arg_n, 0);
-		complete = true;
-	} else {
-		// begin from <resume-file>, <begin-file>, or from scratch with no polycubes
-		let polycube_encodings_read: Vec<u128> = match arg_resume_file.as_ref() {
-			Some(resume_file_path) => {
-				let (
-					resume_n,
-					resume_spawn_n,
-					resume_n_counts,
-					resume_total_elapsed_sec,
-					polycubes_read
-				) = read_resume_file(resume_file_path);
-				arg_n = resume_n;
-				arg_spawn_n = resume_spawn_n;
-				previous_total_elapsed_sec = resume_total_elapsed_sec;
-				unsafe {
-					for (i, count) in resume_n_counts.iter() {
-						N_COUNTS[*i as usize] = *count as usize;
-					}
-				}
-				polycubes_read
-			}
-			None => {
-				match arg_begin_file.as_ref() {
-					Some(begin_file_path) => {
-						// we don't need the value of polycube.n...
-						let (_, polycubes_read) = read_polycubes_file(begin_file_path);
-						polycubes_read
-					}
-					None => {
-						Vec::new()
-					}
-				}
-			}
+	#[test]
+	fn resume_file_round_trip() {
+		let work_dir = std::env::temp_dir().join(format!("cubes-rust-test-resume-{}", std::process::id()));
+		std::fs::create_dir_all(&work_dir).unwrap();
+		let jobs: Vec<u128> = (0..3).map(|r| canonical_enc(6, r * 50)).collect();
+		let frontier: Vec<u128> = vec![canonical_enc(4, 2), canonical_enc(5, 7)];
+		let mut count_status = [CountStatus::Missing; MAX_N];
+		count_status[1..5].fill(CountStatus::Exact);
+		count_status[5..8].fill(CountStatus::Partial);
+		let mut count_projection = CountProjection::new();
+		count_projection.add(JobFeatures::of_encoding(jobs[0]), 120, 3.0);
+		count_projection.add(JobFeatures::of_encoding(jobs[0]), 80, 2.0);
+		let config = RunConfig {
+			n: Some(9),
+			threads: Some(2),
+			spawn_n: Some(6),
+			work_dir: Some(work_dir.clone()),
+			canonicalizer: Some(String::from("lanes")),
+			..Default::default()
 		};
-		let mut polycubes_to_resume: Vec<Polycube> = Vec::new();
-		for polycube_enc in polycube_encodings_read.into_iter() {
-			let mut p = Polycube::new(false);
-			p.rebuild_from_encoding(polycube_enc);
-			polycubes_to_resume.push(p);
-		}
-		if polycubes_to_resume.len() > 0 && polycubes_to_resume.first().unwrap().n >= arg_n {
-			println!("error: n must be larger than the polycubes present in the <resume-file> or <begin-file>");
-			println!("{}", usage);
-			exit(1);
+		write_resume_file(9, 6, jobs.clone(), None, &RunProgress {
+			elapsed_sec: 12.5,
+			completed_jobs: 40,
+			total_jobs: 100,
+			count_status,
+			cost_model: JobCostModel::new(9, 6, false),
+			count_projection: &count_projection,
+			projected_count: None,
+			config: &config,
+			frontier: &frontier
+		});
+		// resume files are written uncompressed, and read once gzipped
+		let resume_file_path = std::fs::read_dir(&work_dir).unwrap()
+			.map(|entry| entry.unwrap().path())
+			.find(|path| path.file_name().unwrap().to_string_lossy().starts_with("halt-n9-"))
+			.unwrap();
+		let gz_path = PathBuf::from(format!("{}.gz", resume_file_path.to_string_lossy()));
+		let mut gz = flate2::write::GzEncoder::new(File::create(&gz_path).unwrap(), flate2::Compression::default());
+		gz.write_all(&std::fs::read(&resume_file_path).unwrap()).unwrap();
+		gz.finish().unwrap();
+
+		let reader = JobFileReader::open(&gz_path, JobFileFormat::Resume);
+		let header = &reader.header;
+		assert_eq!((header.n, header.spawn_n, header.previous_total_elapsed_sec), (9, 6, 12.5));
+		assert_eq!(header.n_counts.len(), MAX_N);
+		assert_eq!((header.job_count, header.completed_jobs, header.total_jobs), (Some(3), Some(40), Some(100)));
+		assert_eq!(header.frontier, frontier);
+		assert_eq!(header.count_status.len(), 9);
+		for (n, status) in header.count_status.iter() {
+			assert_eq!(*status, count_status[*n as usize], "n={}", n);
 		}
+		assert!(header.cost_model.is_some());
+		let strata = header.projection_strata.as_ref().unwrap();
+		let sums = strata.get(&JobFeatures::of_encoding(jobs[0])).unwrap();
+		assert_eq!((sums.samples, sums.sum_y, sums.sum_yy), (2.0, 200.0, 20800.0));
+		let ratio = header.projection_ratio.unwrap();
+		assert_eq!((ratio.samples, ratio.sum_x, ratio.sum_y, ratio.sum_xy), (2.0, 5.0, 200.0, 520.0));
+		let header_config = header.config.as_ref().unwrap();
+		assert_eq!((header_config.n, header_config.threads, header_config.spawn_n), (Some(9), Some(2), Some(6)));
+		assert_eq!(header_config.canonicalizer.as_deref(), Some("lanes"));
+		assert_eq!(header_config.work_dir.as_ref(), Some(&work_dir));
+		assert_eq!(reader.job_count, 3);
+		assert_eq!(reader.collect::<Vec<u128>>(), jobs);
+		std::fs::remove_dir_all(&work_dir).unwrap();
+	}
 
-		let mut saved_worker_jobs: usize = 0;
-		let total_worker_jobs = match arg_spawn_n {
-			0 => {
-				polycubes_to_resume.len()
-			}
-			_ => WELL_KNOWN_N_COUNTS[arg_spawn_n as usize]
-		};
-		let mut compl_worker_jobs: isize = match arg_resume_file.as_ref() {
-			Some(_path) => {
-				(total_worker_jobs - polycubes_to_resume.len()).try_into().unwrap()
-			}
-			None => 0
+	#[test]
+	fn run_config_header_value_round_trip() {
+		let config = RunConfig {
+			n: Some(13),
+			begin_from_file: Some(PathBuf::from("/tmp/jobs n8.txt.gz")),
+			write_found_polycubes_file: Some(String::from("found=1.txt")),
+			checkpoint_minutes: Some(60),
+			output_format: Some(OutputFormat::Json),
+			..Default::default()
 		};
-		println!("to halt early, create the file [{}]", halt_file_path.to_str().unwrap());
-		let mut polycubes_to_write_to_resume_file: Vec<Polycube> = Vec::new();
-		let mut polycubes_to_write_to_polycubes_file: Vec<u128> = Vec::new();
+		let value = config.to_header_value();
+		assert!(!value.contains('\n'), "[{}]", value);
+		let parsed = RunConfig::from_header_value(&value).unwrap();
+		assert_eq!(parsed.n, Some(13));
+		assert_eq!(parsed.threads, None);
+		assert_eq!(parsed.begin_from_file, config.begin_from_file);
+		assert_eq!(parsed.write_found_polycubes_file, config.write_found_polycubes_file);
+		assert_eq!(parsed.checkpoint_minutes, Some(60));
+		assert!(parsed.json_output());
+		assert!(RunConfig::from_header_value("{ n = 13, output_format = \"xml\" }").is_none());
+		assert!(RunConfig::from_header_value("{ n = 13, unknown = 1 }").is_none());
+	}
 
-		// these are the found canonical Polycubes of whatever
-		//   size that the child processes will evaluate
-		// we know that there will not be more Polycubes submitted
-		//   than the number of unique polycubes with n=<spawn-n>
-		let submit_queue: Arc<ArrayQueue<Polycube>> =
-			Arc::new(ArrayQueue::new(total_worker_jobs + 1000));
-		// the child processes will return both counts for fully-
-		//   evaluated Polycubes and also non-evaluated Polycubes
-		//   to write to disk for continuing later
-		// we know that there will not be more responses than
-		//   polycubes submitted, so we can use the same bounding size
-		//   as submit_queue
-		let response_queue: Arc<ArrayQueue<ThreadResponse>> =
-			Arc::new(ArrayQueue::new(total_worker_jobs + 1000));
+	#[test]
+	fn legacy_flags_become_subcommands() {
+		let args = |args: &[&str]| with_legacy_subcommand(args.iter().map(|arg| arg.to_string()).collect());
+		assert_eq!(args(&["cubes", "-n", "9"]), ["cubes", "count", "-n", "9"]);
+		assert_eq!(args(&["cubes", "--threads", "2", "-r", "a.txt.gz"]), ["cubes", "resume", "a.txt.gz", "--threads", "2"]);
+		assert_eq!(args(&["cubes", "--check-canonicalizers", "100"]), ["cubes", "check-canonicalizers", "100"]);
+		assert_eq!(args(&["cubes", "count", "-n", "9"]), ["cubes", "count", "-n", "9"]);
+		assert_eq!(args(&["cubes", "--help"]), ["cubes", "--help"]);
+	}
 
+	// a gzipped resume file for n=7 with the polycubes of size 4 as
+	//   its jobs, of which there are 8 in all
+	fn checked_resume_file(name: &str, completed_jobs: usize, jobs: &[u128]) -> Vec<String> {
+		let path = std::env::temp_dir().join(format!("cubes-rust-test-{}-{}.txt.gz", name, std::process::id()));
+		let mut contents = format!("7\n4\n0\n1=1,2=1,3=2,4=8\njob_count={}\ncompleted_jobs={}\ntotal_jobs=8\n", jobs.len(), completed_jobs);
+		for polycube_enc in jobs {
+			contents.push_str(&format!("{}\n", polycube_enc));
+		}
+		contents.push_str(&format!("{} {}\n", END_MARKER, jobs.len()));
+		let mut gz = flate2::write::GzEncoder::new(File::create(&path).unwrap(), flate2::Compression::default());
+		gz.write_all(contents.as_bytes()).unwrap();
+		gz.finish().unwrap();
+		let problems = check_resume_file(&path).problems;
+		std::fs::remove_file(&path).unwrap();
+		problems
+	}
 
-		// bool for signalling that an early halt has been requested
-		let atomic_halt = Arc::new(AtomicBool::new(false));
-		// bool for signalling to the workers to stop looking for jobs
-		let atomic_done = Arc::new(AtomicBool::new(false));
+	#[test]
+	fn check_resume_file_accepts_valid_file() {
+		let jobs: Vec<u128> = (2..8).map(|r| canonical_enc(4, r)).collect();
+		assert_eq!(checked_resume_file("valid", 2, &jobs), Vec::<String>::new());
+	}
 
-		let mut initial_workers_to_spawn = arg_threads;
-		if arg_resume_file.as_ref().is_none() {
-			// initially spawn threads-1 worker threads, plus one
-			//   thread for the initial work delegator
-			initial_workers_to_spawn -= 1
+	#[test]
+	fn check_resume_file_finds_duplicates() {
+		let mut jobs: Vec<u128> = (2..8).map(|r| canonical_enc(4, r)).collect();
+		jobs.push(jobs[3]);
+		let problems = checked_resume_file("duplicate", 1, &jobs);
+		assert_eq!(problems, vec![format!("job [{}] appears more than once", encoding_to_str(jobs[3]))]);
+	}
+
+	#[test]
+	fn check_resume_file_finds_wrong_size() {
+		let mut jobs: Vec<u128> = (2..8).map(|r| canonical_enc(4, r)).collect();
+		jobs[0] = canonical_enc(5, 0);
+		let problems = checked_resume_file("wrong-size", 2, &jobs);
+		assert_eq!(problems, vec![format!("job [{}] has 5 cubes, not 4", encoding_to_str(jobs[0]))]);
+	}
+
+	#[test]
+	fn check_resume_file_finds_non_canonical() {
+		let mut jobs: Vec<u128> = (2..8).map(|r| canonical_enc(4, r)).collect();
+		let mut p = unrank(4, 2).unwrap();
+		let canonical = p.find_canonical_info(None).enc;
+		// some other start cube and rotation encode the polycube differently
+		let positions: Vec<isize> = p.cube_info_by_pos.keys().copied().collect();
+		let other = positions.iter()
+			.flat_map(|pos| (0..24).filter_map(|rotation| p.make_encoding(*pos, rotation, 0)))
+			.map(|(enc, _pos)| enc)
+			.find(|enc| *enc != canonical)
+			.unwrap();
+		jobs[0] = other;
+		let problems = checked_resume_file("non-canonical", 2, &jobs);
+		assert_eq!(problems.len(), 1);
+		assert!(problems[0].contains("is not canonical"), "{:?}", problems);
+	}
+
+	#[test]
+	fn check_resume_file_checks_bookkeeping() {
+		let jobs: Vec<u128> = (2..8).map(|r| canonical_enc(4, r)).collect();
+		let problems = checked_resume_file("bookkeeping", 5, &jobs);
+		assert_eq!(problems, vec![String::from("5 completed + 6 remaining jobs should be 8, but are 11")]);
+	}
+
+	#[test]
+	fn eta_band_allows_for_parallelism() {
+		// 40 of 100 jobs done, each taking 1 or 3 seconds
+		let mut cost_model = JobCostModel::new(9, 6, false);
+		for i in 0..40 {
+			cost_model.cost.add(1.0, if i % 2 == 0 { 1.0 } else { 3.0 });
 		}
-		let delegator_proc: Option<JoinHandle<()>> = match arg_resume_file.as_ref() {
-			Some(_path) => {
-				for polycube in polycubes_to_resume.into_iter() {
-					match submit_queue.push(polycube) {
-						Ok(_) => {}
-						Err(_) => {
-							println!("error: could not push polycube to submit queue");
-							exit(1);
-						}
-					}
-				}
-				None
-			}
-			None => {
-				match arg_begin_file.as_ref() {
-					Some(_path) => {
-						let begin_n = polycubes_to_resume.first().unwrap().n;
-						unsafe {
-							N_COUNTS[begin_n as usize] = polycubes_to_resume.len();
-						}
-						for polycube in polycubes_to_resume.into_iter() {
-							match submit_queue.push(polycube) {
-								Ok(_) => {}
-								Err(_) => {
-									println!("error: could not push polycube to submit queue");
-									exit(1);
-								}
-							}
-						}
-						None
-					}
-					None => {
-						unsafe {
-							N_COUNTS[1] = 1;
-						}
-						let ah = atomic_halt.clone();
-						let sq = submit_queue.clone();
-						let rq = response_queue.clone();
-						let handle = thread::spawn(move || {
-							let mut polycube = Polycube::new(true);
-							extend_and_delegate_outer(&mut polycube, arg_n, ah, sq, rq, arg_spawn_n);
-						});
-						Some(handle)
-					}
-				}
+		// the 60 remaining jobs take about 120 seconds on 4 threads
+		let parallelism = Parallelism::new(4, 60, 40.0, 10.0);
+		let estimate = cost_model.estimate(100, 40, &parallelism).unwrap();
+		assert!((estimate.remaining_sec - 30.0).abs() < 1e-9, "{}", estimate.remaining_sec);
+		assert!(estimate.remaining_sec_low < 30.0 && estimate.remaining_sec_high > 30.0);
+		// if the threads have only been half busy, they may stay that way
+		let parallelism = Parallelism::new(4, 60, 20.0, 10.0);
+		let estimate = cost_model.estimate(100, 40, &parallelism).unwrap();
+		assert!((estimate.remaining_sec - 30.0).abs() < 1e-9, "{}", estimate.remaining_sec);
+		assert!(estimate.remaining_sec_high > 60.0, "{}", estimate.remaining_sec_high);
+		// fewer jobs than threads remain
+		let parallelism = Parallelism::new(4, 2, 40.0, 10.0);
+		assert_eq!((parallelism.expected, parallelism.low, parallelism.high), (2.0, 2.0, 2.0));
+	}
+
+	#[test]
+	fn projection_covers_final_count() {
+		// a run for n=9 with spawn_n=6 (so probe_n=8) on 4 threads,
+		//   where the jobs are handed out in the order they're found and
+		//   take time in proportion to their counts, so the cheap ones
+		//   tend to complete first
+		let (n, spawn_n, probe_n, threads) = (9, 6, 8, 4);
+		let mut jobs: Vec<(u128, usize, usize)> = Vec::new();
+		for_each_polycube(spawn_n, &Arc::new(AtomicBool::new(false)), |polycube_enc| {
+			let counts = subtree_counts(polycube_enc, n, 0, 0);
+			jobs.push((polycube_enc, counts[probe_n], counts[n as usize]));
+		});
+		let mut in_flight: Vec<(usize, usize)> = Vec::new();
+		let mut completion_order: Vec<usize> = Vec::new();
+		while completion_order.len() < jobs.len() {
+			let finish_time = in_flight.iter().map(|(finish_time, _job)| *finish_time).min().unwrap_or(0);
+			if in_flight.len() < threads && completion_order.len() + in_flight.len() < jobs.len() {
+				let job = completion_order.len() + in_flight.len();
+				in_flight.push((finish_time + jobs[job].2 + 1, job));
+				continue;
 			}
-		};
-		let mut worker_handles: Vec<JoinHandle<()>> = Vec::new();
-		let mut worker_waiting_bools: Vec<Arc<AtomicBool>> = Vec::new();
-		let arg_write_polycubes_file: bool = arg_polycubes_file.is_some();
-		for _i in 0..initial_workers_to_spawn {
-			let ah = atomic_halt.clone();
-			let ad = atomic_done.clone();
-			let aw = Arc::new(AtomicBool::new(false));
-			let awc = aw.clone();
-			worker_waiting_bools.push(aw);
-			let sq = submit_queue.clone();
-			let rq = response_queue.clone();
-			let handle = thread::spawn(move || {
-				extend_as_worker_outer(arg_n, ah, ad, awc, sq, rq, arg_write_polycubes_file);
-			});
-			worker_handles.push(handle);
+			let i = in_flight.iter().position(|(t, _job)| *t == finish_time).unwrap();
+			completion_order.push(in_flight.remove(i).1);
 		}
-		let mut halted = false;
-		let mut last_stats_and_halt = Instant::now();
-		while !halted || !submit_queue.is_empty() || !response_queue.is_empty() {
-			// once the initial work delegator has finished,
-			//   spawn a new worker thread
-			if !halted && delegator_proc.is_some()
-					&& delegator_proc.as_ref().unwrap().is_finished() && worker_handles.len() < arg_threads as usize {
-				println!("\ninitial delegator thread has finished, spawning a new worker thread");
-				// the initial delegator thread submits its results through
-				//   the same response_queue as the rest of the workers, but it shouldn't
-				//   be counted as a completed worker job
-				compl_worker_jobs -= 1;
-				let ah = atomic_halt.clone();
-				let ad = atomic_done.clone();
-				let aw = Arc::new(AtomicBool::new(false));
-				let awc = aw.clone();
-				worker_waiting_bools.push(aw);
-				let sq = submit_queue.clone();
-				let rq = response_queue.clone();
-				let handle = thread::spawn(move || {
-					extend_as_worker_outer(arg_n, ah, ad, awc, sq, rq, arg_write_polycubes_file);
-				});
-				worker_handles.push(handle);
-			}
-			// check for halt file
-			if !halted {
-				if halt_file_path.exists() {
-					println!("\nfound halt file [{}], stopping...", halt_file_path.to_str().unwrap());
-					// signal to the threads that they should stop
-					atomic_halt.store(true, Ordering::Relaxed);
-					halted = true;
-				}
-			}
-			// check if everything has completed
-			if !halted && (delegator_proc.is_none() || delegator_proc.as_ref().unwrap().is_finished())
-					&& submit_queue.is_empty() {
-				let all_workers_waiting = worker_waiting_bools.iter().all(|w| w.load(Ordering::Acquire));
-				if all_workers_waiting {
-					println!("\nlooks like we have finished!  stopping...");
-					// signal to the threads that they should stop
-					atomic_done.store(true, Ordering::Relaxed);
-					halted = true;
-					complete = true;
-				}
-			}
 
-			// regardless of halt file, continue to process responses
-			//   sent by the workers (if any are currently available)
-			let mut found_something = true;
-			while found_something {
-				if last_stats_and_halt.elapsed().as_secs_f32() > 1.0 {
-					last_stats_and_halt = Instant::now();
-					// check for halt file
-					if !halted && !complete && halt_file_path.exists() {
-						println!("\nfound halt file [{}], stopping...", halt_file_path.to_str().unwrap());
-						// signal to the threads that they should stop
-						atomic_halt.store(true, Ordering::Relaxed);
-						halted = true;
-					}
-					// print stats
-					if compl_worker_jobs > 0 {
-						let time_elapsed = start_time.elapsed();
-						let seconds_per_thread = (time_elapsed.as_secs_f64() + previous_total_elapsed_sec) / (compl_worker_jobs as f64);
-						let threads_remaining = (total_worker_jobs as isize - compl_worker_jobs) as f64;
-						let seconds_remaining = threads_remaining * seconds_per_thread;
-						let pct_complete = (compl_worker_jobs as f64 * 100.0) / (total_worker_jobs as f64);
-						let total_seconds = seconds_remaining + time_elapsed.as_secs_f64() + previous_total_elapsed_sec;
-						print!("    {:.4}% complete, ETA:[{}], total:[{}], counting for n={}:[{}], outstanding threads:[{}-{}={}]        \r",
-							pct_complete,
-							seconds_to_dur(seconds_remaining),
-							seconds_to_dur(total_seconds),
-							arg_n,
-							unsafe { N_COUNTS[arg_n as usize] },
-							total_worker_jobs,
-							compl_worker_jobs,
-							total_worker_jobs as isize - compl_worker_jobs);
-						std::io::stdout().flush().unwrap();
-					}
-				}
-				let response = response_queue.pop();
-				if response.is_none() {
-					found_something = false;
-					continue;
-				}
-				let response = response.unwrap();
-				if response.job_complete {
-					// we have a completed job
-					//   (either a fully-evaluated polycube or a non-evaluated polycube)
-					//   so we can increment the completed worker jobs count
-					compl_worker_jobs += 1;
-					// if we have a fully-evaluated polycube, we can increment the
-					//   count for that polycube's n
-					if response.results.is_some() {
-						let results = response.results.unwrap();
-						unsafe {
-							for i in 1..arg_n+1 {
-								N_COUNTS[i as usize] += results[i as usize];
-							}
-						}
-					}
-					last_count_increment_time = Some(Instant::now());
-					if response.final_polycube_encs.len() > 0 {
-						polycubes_to_write_to_polycubes_file.extend(response.final_polycube_encs);
-						if polycubes_to_write_to_polycubes_file.len() >= 10000 {
-							write_polycubes_file(arg_n, arg_polycubes_file_path, &mut polycubes_to_write_to_polycubes_file);
-							polycubes_to_write_to_polycubes_file.clear();
-						}
-					} else if arg_write_polycubes_file {
-						println!("\nresponse without any final polycubes!");
-					}
-				} else {
-					match response.orig_polycube {
-						// we have a non-evaluated polycube, so we can write it to disk
-						Some(polycube) => {
-							polycubes_to_write_to_resume_file.push(polycube);
-							saved_worker_jobs += 1;
-						}
-						None => {
-							println!("\nthe initial delegator worker was halted.  thus, we cannot resume from this point later and will not write any data to disk")
-						}
-					}
-				}
+		let final_count = WELL_KNOWN_N_COUNTS[n as usize] as f64;
+		let mut count_projection = CountProjection::new();
+		let mut counted: usize = 0;
+		let mut remaining_probe: usize = jobs.iter().map(|(_polycube_enc, probe, _count)| probe).sum();
+		let (mut projections, mut covered, mut later_projections, mut later_covered) = (0, 0, 0, 0);
+		for (completed, job) in completion_order.iter().enumerate().map(|(i, job)| (i + 1, *job)) {
+			let (polycube_enc, probe, count) = jobs[job];
+			count_projection.add(JobFeatures::of_encoding(polycube_enc), count, probe as f64);
+			counted += count;
+			remaining_probe -= probe;
+			if completed % 5 != 0 || completed < 5 || completed + 5 > jobs.len() {
+				continue;
+			}
+			let projected = count_projection.project(counted, Some(remaining_probe as f64), jobs.len()).unwrap();
+			assert!((projected.value - final_count).abs() < 0.1 * final_count, "{} completed: {}", completed, projected.describe());
+			let is_covered = projected.low <= final_count && final_count <= projected.high;
+			projections += 1;
+			covered += is_covered as usize;
+			// (the first jobs to be found are alike, so the first few
+			//   projections are the least reliable)
+			if completed * 3 >= jobs.len() {
+				later_projections += 1;
+				later_covered += is_covered as usize;
 			}
-			thread::sleep(Duration::from_millis(1000));
 		}
-		// we probably don't need to join, but it would be nice to
-		//   figure out how to do that here
-		for w in worker_handles.into_iter() {
-			w.join().unwrap();
+		assert!(covered * 4 >= projections * 3, "covered {} of {}", covered, projections);
+		assert!(later_covered * 10 >= later_projections * 9, "covered {} of {}", later_covered, later_projections);
+	}
+
+	fn polycubes_state(contents: &str) -> Result<PolycubesFileState, String> {
+		polycubes_state_from_reader(contents.as_bytes(), 5)
+	}
+
+	#[test]
+	fn polycubes_state_complete() {
+		assert_eq!(polycubes_state("5\n"), Ok(PolycubesFileState::Complete { len: 2 }));
+		let contents = "5\nabc\ndef\n--end-- 2\nghi\n--end-- 1\n";
+		assert_eq!(polycubes_state(contents), Ok(PolycubesFileState::Complete { len: contents.len() as u64 }));
+	}
+
+	#[test]
+	fn polycubes_state_truncated() {
+		let complete = "5\nabc\ndef\n--end-- 2\n";
+		// cut off part way through a batch, a line, or a trailer
+		for rest in ["ghi\n", "gh", "ghi\n--end-- 1", "ghi\n--end-- 2\n"] {
+			let contents = format!("{}{}", complete, rest);
+			assert_eq!(polycubes_state(&contents),
+				Ok(PolycubesFileState::Interrupted { complete_len: complete.len() as u64, len: contents.len() as u64 }), "[{}]", rest);
 		}
-		println!("\nall threads have completed: compl_worker_jobs={}, saved_worker_jobs={} (compl+saved={}, where @n={} should be total_worker_jobs={})",
-			compl_worker_jobs, saved_worker_jobs, compl_worker_jobs + saved_worker_jobs as isize, arg_spawn_n, total_worker_jobs);
-		if polycubes_to_write_to_resume_file.len() > 0 {
-			write_resume_file(
-				arg_n,
-				arg_spawn_n,
-				polycubes_to_write_to_resume_file,
-				previous_total_elapsed_sec + (last_count_increment_time.unwrap().duration_since(start_time).as_secs_f64()),
-			)
+	}
+
+	#[test]
+	fn polycubes_state_legacy() {
+		for contents in ["5\nabc\ndef\n", "5\nabc\nde"] {
+			assert_eq!(polycubes_state(contents), Ok(PolycubesFileState::Legacy { len: contents.len() as u64 }));
 		}
-		if polycubes_to_write_to_polycubes_file.len() > 0 {
-			write_polycubes_file(arg_n, arg_polycubes_file_path, &mut polycubes_to_write_to_polycubes_file);
+	}
+
+	#[test]
+	fn sampler_reaches_every_polycube() {
+		let samples = sample_polycubes(5, 600, 8, 1, 2);
+		assert_eq!(samples.len(), 600);
+		let distinct: BTreeSet<u128> = samples.iter().map(|(polycube_enc, _effective_walks)| *polycube_enc).collect();
+		assert_eq!(distinct.len(), WELL_KNOWN_N_COUNTS[5]);
+		for polycube_enc in distinct {
+			let mut polycube = Polycube::new(false);
+			polycube.rebuild_from_encoding(polycube_enc);
+			assert_eq!(polycube.n, 5);
+			assert_eq!(polycube.find_canonical_info(None).enc, polycube_enc);
 		}
 	}
-	if last_count_increment_time.is_none() {
-		last_count_increment_time = Some(Instant::now());
+
+	#[test]
+	fn sampler_repeats_with_seed() {
+		assert_eq!(sample_polycubes(6, 20, 20, 7, 1), sample_polycubes(6, 20, 20, 7, 3));
 	}
-	print_results(complete, arg_n);
-	if last_count_increment_time.is_none() {
-		last_count_increment_time = Some(Instant::now());
+
+	#[test]
+	fn sampler_is_uniform() {
+		let p_value = check_sampler_uniformity(5, 1000, 16, 11, 2).unwrap();
+		assert!(p_value >= 0.001, "p-value={}", p_value);
 	}
-	let last_count_increment_time = last_count_increment_time.unwrap();
-	let time_elapsed = last_count_increment_time.duration_since(start_time);
-	if arg_resume_file.as_ref().is_none() {
-		println!("elapsed seconds: {}.{}", time_elapsed.as_secs(), time_elapsed.subsec_micros());
-	} else {
-		let total_time_elapsed = time_elapsed.as_secs_f64() + previous_total_elapsed_sec;
-		println!("elapsed seconds: {:.6} + {:.6} (previously) = {:.6}", time_elapsed.as_secs_f64(), previous_total_elapsed_sec, total_time_elapsed);
+
+	#[test]
+	fn sampler_is_fast_at_n15() {
+		// (the sampler this replaced took hours at n=15, this takes a
+		//   fraction of a second in a release build)
+		let start_time = Instant::now();
+		let (polycube_enc, effective_walks) = sample_polycube(15, 100, &mut StdRng::seed_from_u64(5));
+		assert!(start_time.elapsed().as_secs_f64() < 30.0);
+		assert!((1.0..=100.0).contains(&effective_walks));
+		let mut polycube = Polycube::new(false);
+		polycube.rebuild_from_encoding(polycube_enc);
+		assert_eq!(polycube.n, 15);
 	}
 
+	#[test]
+	fn polycubes_state_other_n() {
+		assert!(polycubes_state("6\nabc\n--end-- 1\n").is_err());
+		assert!(polycubes_state("").is_err());
+	}
 }